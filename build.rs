@@ -0,0 +1,91 @@
+//! Rasterizes the tray icon from vector primitives at build time.
+//!
+//! Every size in `SIZES` is written to `OUT_DIR` as raw RGBA in a black glyph
+//! (macOS template / light menu bars) and a white glyph (dark menu bars).
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Menu bar icons are 18pt tall; render 1x, 2x and 3x.
+const SIZES: [u32; 3] = [18, 36, 54];
+/// Subsamples per axis used for anti-aliasing.
+const SUPERSAMPLE: u32 = 4;
+
+const STROKE: f32 = 0.085;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    for size in SIZES {
+        let coverage = rasterize(size);
+        for (variant, level) in [("black", 0u8), ("white", 255u8)] {
+            let mut rgba = Vec::with_capacity(coverage.len() * 4);
+            for alpha in &coverage {
+                rgba.extend_from_slice(&[level, level, level, *alpha]);
+            }
+            let path = Path::new(&out_dir).join(format!("tray-{}-{}.rgba", size, variant));
+            fs::write(&path, rgba).expect("failed to write tray icon");
+        }
+    }
+}
+
+fn rasterize(size: u32) -> Vec<u8> {
+    let samples = (SUPERSAMPLE * SUPERSAMPLE) as f32;
+    let mut coverage = Vec::with_capacity((size * size) as usize);
+    for py in 0..size {
+        for px in 0..size {
+            let mut hits = 0u32;
+            for sy in 0..SUPERSAMPLE {
+                for sx in 0..SUPERSAMPLE {
+                    let x = (px as f32 + (sx as f32 + 0.5) / SUPERSAMPLE as f32) / size as f32;
+                    let y = (py as f32 + (sy as f32 + 0.5) / SUPERSAMPLE as f32) / size as f32;
+                    if inside_glyph(x, y) {
+                        hits += 1;
+                    }
+                }
+            }
+            coverage.push((hits as f32 / samples * 255.0).round() as u8);
+        }
+    }
+    coverage
+}
+
+/// A clipboard outline with its clip and a right-pointing flow arrow, in unit
+/// coordinates with the origin at the top-left.
+fn inside_glyph(x: f32, y: f32) -> bool {
+    let board = rounded_rect(x, y, (0.17, 0.13), (0.83, 0.95), 0.12);
+    let board_outline = (-STROKE..=0.0).contains(&board);
+    let clip = rounded_rect(x, y, (0.33, 0.05), (0.67, 0.23), 0.05) <= 0.0;
+    // Keep a gap between the clip and the board outline so they read apart.
+    let clip_gap = rounded_rect(
+        x,
+        y,
+        (0.33 - STROKE, 0.05),
+        (0.67 + STROKE, 0.23 + STROKE),
+        0.05,
+    ) <= 0.0;
+    let shaft = rounded_rect(x, y, (0.31, 0.52), (0.56, 0.61), 0.0) <= 0.0;
+    let head = in_triangle(x, y, (0.54, 0.40), (0.54, 0.73), (0.71, 0.565));
+    clip || (board_outline && !clip_gap) || shaft || head
+}
+
+/// Signed distance from `(x, y)` to a rounded rectangle; negative inside.
+fn rounded_rect(x: f32, y: f32, min: (f32, f32), max: (f32, f32), radius: f32) -> f32 {
+    let cx = (min.0 + max.0) / 2.0;
+    let cy = (min.1 + max.1) / 2.0;
+    let hx = (max.0 - min.0) / 2.0 - radius;
+    let hy = (max.1 - min.1) / 2.0 - radius;
+    let dx = (x - cx).abs() - hx;
+    let dy = (y - cy).abs() - hy;
+    let outside = (dx.max(0.0).powi(2) + dy.max(0.0).powi(2)).sqrt();
+    outside + dx.max(dy).min(0.0) - radius
+}
+
+fn in_triangle(x: f32, y: f32, a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> bool {
+    let edge = |p: (f32, f32), q: (f32, f32)| (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0);
+    let (d1, d2, d3) = (edge(a, b), edge(b, c), edge(c, a));
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(negative && positive)
+}
@@ -0,0 +1,61 @@
+//! Arbitrary UTF-8 through the detectors, the regex tester, and every
+//! transform; the same targets as `pasteflow soak`, driven by libFuzzer.
+#![no_main]
+#![allow(dead_code)]
+
+#[path = "../../src/autocorrect.rs"]
+mod autocorrect;
+#[path = "../../src/clock.rs"]
+mod clock;
+#[path = "../../src/command.rs"]
+mod command;
+#[path = "../../src/compress.rs"]
+mod compress;
+#[path = "../../src/corpus.rs"]
+mod corpus;
+#[path = "../../src/curl.rs"]
+mod curl;
+#[path = "../../src/detect.rs"]
+mod detect;
+#[path = "../../src/emoji.rs"]
+mod emoji;
+#[path = "../../src/flatten.rs"]
+mod flatten;
+#[path = "../../src/html.rs"]
+mod html;
+#[path = "../../src/ip.rs"]
+mod ip;
+#[path = "../../src/mdlinks.rs"]
+mod mdlinks;
+#[path = "../../src/numbers.rs"]
+mod numbers;
+#[path = "../../src/paths.rs"]
+mod paths;
+#[path = "../../src/plugins.rs"]
+mod plugins;
+#[path = "../../src/rules.rs"]
+mod rules;
+#[path = "../../src/script.rs"]
+mod script;
+#[path = "../../src/soak.rs"]
+mod soak;
+#[path = "../../src/table.rs"]
+mod table;
+#[path = "../../src/transforms.rs"]
+mod transforms;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = detect::detect_content_types(input);
+    let _ = detect::parse_timestamp(input, clock::global(), false);
+    // The first line doubles as a user-supplied regex.
+    let pattern = input.lines().next().unwrap_or("");
+    let _ = rules::test_regex(pattern, input);
+    for kind in soak::transforms() {
+        let _ = kind.apply(input);
+    }
+});
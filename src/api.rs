@@ -0,0 +1,265 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Read-only requests served by the local HTTP API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiRequest {
+    GetState,
+    /// Rule counters in Prometheus text format.
+    Metrics,
+    /// Rule counters as JSON.
+    MetricsJson,
+}
+
+#[derive(Debug)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+/// A request handed to the event loop, answered through `reply`.
+#[derive(Debug)]
+pub struct ApiCall {
+    pub request: ApiRequest,
+    pub reply: mpsc::Sender<ApiResponse>,
+}
+
+impl ApiResponse {
+    pub fn json(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    pub fn prometheus(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/plain; version=0.0.4; charset=utf-8",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{}\n", message),
+        }
+    }
+}
+
+/// Who may call the API: requests must name the bound port in `Host` and
+/// carry the configured token.
+#[derive(Debug, Clone)]
+struct Access {
+    port: u16,
+    token: String,
+}
+
+/// Bind the API listener and serve it on a background thread.
+///
+/// Each accepted request is forwarded to `dispatch`; the connection waits for the
+/// event loop to answer on the call's reply channel. Refuses to start without
+/// a token.
+pub fn spawn<F>(bind: &str, token: &str, dispatch: F) -> std::io::Result<()>
+where
+    F: Fn(ApiCall) -> bool + Send + Sync + 'static,
+{
+    if token.trim().is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "api.token is not set",
+        ));
+    }
+    let listener = TcpListener::bind(bind)?;
+    let access = std::sync::Arc::new(Access {
+        port: listener.local_addr()?.port(),
+        token: token.trim().to_string(),
+    });
+    let dispatch = std::sync::Arc::new(dispatch);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let dispatch = dispatch.clone();
+            let access = access.clone();
+            std::thread::spawn(move || {
+                let _ = handle_connection(stream, &access, dispatch.as_ref());
+            });
+        }
+    });
+    Ok(())
+}
+
+fn handle_connection<F>(mut stream: TcpStream, access: &Access, dispatch: &F) -> std::io::Result<()>
+where
+    F: Fn(ApiCall) -> bool,
+{
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Keep `Host` and `Authorization`; the API has no request bodies.
+    let (mut host, mut authorization) = (None, None);
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim().to_string();
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value);
+            } else if name.trim().eq_ignore_ascii_case("authorization") {
+                authorization = Some(value);
+            }
+        }
+        header.clear();
+    }
+
+    let response = match authorize(host.as_deref(), authorization.as_deref(), access)
+        .and_then(|()| parse_request(&request_line))
+    {
+        Ok(request) => {
+            let (tx, rx) = mpsc::channel();
+            if dispatch(ApiCall { request, reply: tx }) {
+                rx.recv_timeout(REPLY_TIMEOUT)
+                    .unwrap_or_else(|_| ApiResponse::error(503, "Pasteflow did not respond"))
+            } else {
+                ApiResponse::error(503, "Pasteflow is shutting down")
+            }
+        }
+        Err(response) => response,
+    };
+    write_response(&mut stream, &response)
+}
+
+/// Check `Host` first: a page that rebinds its own domain to 127.0.0.1 can
+/// reach the port, but its requests still name that domain.
+fn authorize(
+    host: Option<&str>,
+    authorization: Option<&str>,
+    access: &Access,
+) -> Result<(), ApiResponse> {
+    let local = host.is_some_and(|host| {
+        ["127.0.0.1", "localhost", "[::1]"]
+            .iter()
+            .any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, access.port)))
+    });
+    if !local {
+        return Err(ApiResponse::error(403, "Forbidden"));
+    }
+    let token = authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    if !token.is_some_and(|token| same_token(token, &access.token)) {
+        return Err(ApiResponse::error(401, "Missing or wrong API token"));
+    }
+    Ok(())
+}
+
+/// Compare without stopping at the first difference, so timing does not
+/// reveal how much of a guess was right.
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn parse_request(line: &str) -> Result<ApiRequest, ApiResponse> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or("");
+    if method != "GET" {
+        return Err(ApiResponse::error(405, "Method not allowed"));
+    }
+    match path {
+        "/state" => Ok(ApiRequest::GetState),
+        "/metrics" => Ok(ApiRequest::Metrics),
+        "/metrics.json" => Ok(ApiRequest::MetricsJson),
+        _ => Err(ApiResponse::error(404, "Not found")),
+    }
+}
+
+fn write_response(stream: &mut TcpStream, response: &ApiResponse) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_request_routes_get_paths() {
+        assert_eq!(
+            parse_request("GET /state HTTP/1.1\r\n").unwrap(),
+            ApiRequest::GetState
+        );
+        assert_eq!(
+            parse_request("GET /metrics?x=1 HTTP/1.1").unwrap(),
+            ApiRequest::Metrics
+        );
+        assert_eq!(
+            parse_request("GET /metrics.json HTTP/1.1").unwrap(),
+            ApiRequest::MetricsJson
+        );
+        assert_eq!(parse_request("GET /nope HTTP/1.1").unwrap_err().status, 404);
+        assert_eq!(
+            parse_request("POST /state HTTP/1.1").unwrap_err().status,
+            405
+        );
+        assert_eq!(parse_request("").unwrap_err().status, 405);
+    }
+
+    #[test]
+    fn requests_need_a_local_host_and_the_token() {
+        let access = Access {
+            port: 7433,
+            token: "s3cret".to_string(),
+        };
+        let status = |host: Option<&str>, authorization: Option<&str>| {
+            authorize(host, authorization, &access)
+                .err()
+                .map(|e| e.status)
+        };
+        assert_eq!(status(Some("127.0.0.1:7433"), Some("Bearer s3cret")), None);
+        assert_eq!(status(Some("LOCALHOST:7433"), Some("Bearer s3cret")), None);
+        assert_eq!(
+            status(Some("evil.example:7433"), Some("Bearer s3cret")),
+            Some(403)
+        );
+        assert_eq!(
+            status(Some("127.0.0.1:80"), Some("Bearer s3cret")),
+            Some(403)
+        );
+        assert_eq!(status(None, Some("Bearer s3cret")), Some(403));
+        assert_eq!(
+            status(Some("127.0.0.1:7433"), Some("Bearer s3cre")),
+            Some(401)
+        );
+        assert_eq!(status(Some("127.0.0.1:7433"), None), Some(401));
+    }
+}
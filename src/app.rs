@@ -1,26 +1,43 @@
+use crate::api::{self, ApiCall, ApiRequest, ApiResponse};
+use crate::browser;
+use crate::catalog;
+use crate::chunks;
 use crate::config;
-use crate::detect;
+use crate::detect::{self, ContentType};
 use crate::diff;
-use crate::rules::{MatchContext, Rule, Suggestion};
+use crate::history::{self, HistoryItem};
+use crate::images::{self, ClipboardImage};
+use crate::ime;
+use crate::journal::{self, JournalEntry};
+use crate::metrics::Metrics;
+use crate::paste;
+use crate::plugins;
+use crate::policy::{self, Decision, PolicyAction};
+use crate::rules::{self, MatchContext, RegexTest, Rule, Suggestion};
+use crate::session::{self, Session};
 use crate::transforms::TransformKind;
 use arboard::Clipboard;
-use chrono::{DateTime, Local, Utc};
-use enigo::{Enigo, Key, KeyboardControllable};
+use chrono::{Local, Utc};
 use global_hotkey::{
     GlobalHotKeyEvent, GlobalHotKeyManager,
     hotkey::{Code, HotKey, Modifiers},
 };
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
-use std::sync::Arc;
-use tray_icon::menu::{Menu, MenuEvent, MenuId, MenuItem};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tray_icon::menu::{
+    CheckMenuItem, Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu,
+};
 use tray_icon::{TrayIcon, TrayIconBuilder};
 use winit::application::ApplicationHandler;
 use winit::dpi::LogicalSize;
 use winit::event::WindowEvent;
 use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
-use winit::window::{Window, WindowId};
+use winit::window::{Theme, Window, WindowId};
 use wry::http::Request;
 use wry::{WebView, WebViewBuilder};
 
@@ -30,14 +47,27 @@ struct PanelState {
     diff: String,
     error: Option<String>,
     active_app: Option<String>,
-    content_types: Vec<crate::detect::ContentType>,
+    /// Process of `active_app`, the paste destination.
+    active_pid: Option<u64>,
+    /// Bundle identifier or executable name of `active_app`.
+    active_app_id: Option<String>,
+    /// Tab URL when `active_app` is a browser.
+    active_url: Option<String>,
+    content_types: Vec<(ContentType, f32)>,
     active_app_key: String,
     search_query: Option<String>,
+    image: Option<ClipboardImage>,
+    /// Description of non-text, non-image clipboard data, such as copied files.
+    binary: Option<String>,
+    /// `content_hash` of the clipboard text `input` was read from; `None` when
+    /// the input came from elsewhere (history, a restored session) or is an image.
+    clipboard_hash: Option<String>,
 }
 
 struct AppState {
     cfg: config::Config,
-    clipboard: Clipboard,
+    /// `None` while the clipboard is unavailable; reopened on next use.
+    clipboard: Option<Clipboard>,
     suggestions: Vec<Suggestion>,
     selected_rule_id: Option<String>,
     panel: PanelState,
@@ -45,11 +75,31 @@ struct AppState {
     config_error: Option<String>,
     config_draft_error: Option<String>,
     config_diff: Option<String>,
-    hotkey_manager: GlobalHotKeyManager,
+    hotkey_manager: Option<GlobalHotKeyManager>,
     registered_hotkeys: Vec<HotKey>,
     hotkey_map: HashMap<u32, HotkeyRule>,
     hotkey_warnings: Vec<String>,
     history: Vec<HistoryItem>,
+    metrics: Metrics,
+    /// Operation journaled before the last run ended without completing it.
+    interrupted: Option<JournalEntry>,
+    regex_test: Option<Result<RegexTest, String>>,
+    /// Per-step outputs of the selected rule, computed on request.
+    pipeline_trace: Option<UiPipelineTrace>,
+    /// What `[[policies]]` say about pasting the current output.
+    policy: Option<Decision>,
+    /// Index of the next chunk `PasteNextChunk` pastes; 0 when none have been.
+    next_chunk: usize,
+    /// `content_hash` of the last sensitive output pasted or copied; matching
+    /// text is masked in the panel.
+    masked_hash: Option<String>,
+    /// Features that failed to start; the rest of the app keeps working.
+    health: Vec<UiHealthIssue>,
+    /// Panel saved when the app last quit, until restored or dismissed.
+    last_session: Option<Session>,
+    /// `panel_hash` of the input and output last pasted or copied; that work
+    /// is done, so it is not saved as a session.
+    done_hash: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -57,18 +107,86 @@ struct AppState {
 enum IpcMessage {
     Paste,
     Copy,
+    /// Put the before/after diff on the clipboard instead of the output.
+    CopyDiff {
+        /// Wrap it in a Markdown ```` ```diff ```` block.
+        #[serde(default)]
+        markdown: bool,
+    },
     Cancel,
-    SelectRule { id: String },
-    ToggleAutoAccept { id: String, value: bool },
-    TogglePinned { id: String, value: bool },
-    UpdateRuleDescription { id: String, value: String },
-    UpdateHotkeyCombo { combo: String },
-    UpdateHotkeyApp { app: String, combo: String },
-    RemoveHotkeyApp { app: String },
-    UpdateSearch { value: String },
+    /// The panel page finished loading (first launch or a dev-mode reload).
+    Ready,
+    SelectRule {
+        id: String,
+    },
+    ToggleAutoAccept {
+        id: String,
+        value: bool,
+    },
+    TogglePinned {
+        id: String,
+        value: bool,
+    },
+    UpdateRuleDescription {
+        id: String,
+        value: String,
+    },
+    RenameRule {
+        old_id: String,
+        new_id: String,
+    },
+    UpdateHotkeyCombo {
+        combo: String,
+    },
+    UpdateHotkeyApp {
+        app: String,
+        combo: String,
+    },
+    RemoveHotkeyApp {
+        app: String,
+    },
+    UpdateSearch {
+        value: String,
+    },
+    ClearUiState,
+    InstallPreset {
+        id: String,
+    },
+    /// Add a rule that runs the installed plugin `name`.
+    InstallPlugin {
+        name: String,
+    },
     RequestConfig,
-    UpdateConfigDraft { raw: String },
-    SaveConfig { raw: String },
+    UpdateConfigDraft {
+        raw: String,
+    },
+    SaveConfig {
+        raw: String,
+    },
+    /// Put back the clipboard from before the interrupted operation.
+    RestoreInterrupted,
+    DismissInterrupted,
+    RestoreSession,
+    DismissSession,
+    /// Load the joined outputs of several history entries into the panel,
+    /// optionally selecting `rule` to transform them.
+    MergeHistory {
+        indexes: Vec<usize>,
+        #[serde(default = "default_merge_separator")]
+        separator: String,
+        #[serde(default)]
+        rule: Option<String>,
+    },
+    /// Run a matcher regex against `sample`, or the clipboard text when omitted.
+    TestRegex {
+        pattern: String,
+        #[serde(default)]
+        sample: Option<String>,
+    },
+    /// Run the selected rule's transforms one at a time, keeping each output.
+    RequestPipelineTrace,
+    /// Paste the next numbered chunk of an output over the app's `max_paste_chars`.
+    PasteNextChunk,
 }
 
 #[derive(Debug, Serialize)]
@@ -78,9 +196,11 @@ struct UiRule {
     auto_accept: bool,
     uses_remote: bool,
     pinned: bool,
+    sensitive: bool,
     score: i32,
     detail: String,
     match_hint: String,
+    prompt: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -93,6 +213,8 @@ struct UiState {
     selected_rule_id: Option<String>,
     active_app: Option<String>,
     content_types: Vec<String>,
+    image: Option<String>,
+    binary: Option<String>,
     search_query: Option<String>,
     config: UiConfigState,
     config_text: Option<String>,
@@ -102,13 +224,99 @@ struct UiState {
     history: Vec<UiHistoryItem>,
     stats: UiStats,
     error: Option<String>,
+    interrupted: Option<UiInterrupted>,
+    regex_test: Option<RegexTest>,
+    regex_test_error: Option<String>,
+    pipeline_trace: Option<UiPipelineTrace>,
+    policy: Option<UiPolicy>,
+    paste_limit: Option<UiPasteLimit>,
+    health: Vec<UiHealthIssue>,
+    last_session: Option<UiSession>,
+}
+
+/// What `GET /state` serves. It carries no clipboard text, history, or config
+/// source, and nothing derived from a sensitive rule's output.
+#[derive(Debug, Serialize)]
+struct ApiState {
+    active_app: Option<String>,
+    content_types: Vec<String>,
+    selected_rule_id: Option<String>,
+    suggestions: Vec<ApiRule>,
+    /// Counts for the input and output; `None` while a sensitive rule is selected.
+    stats: Option<UiStats>,
+    error: Option<String>,
+    policy: Option<UiPolicy>,
+    health: Vec<UiHealthIssue>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiRule {
+    id: String,
+    name: String,
+    score: i32,
+}
+
+/// `[[policies]]` that fired for the current output.
+#[derive(Debug, Serialize)]
+struct UiPolicy {
+    action: PolicyAction,
+    reasons: Vec<String>,
+}
+
+/// The output is longer than the active app's `max_paste_chars`.
+#[derive(Debug, Serialize)]
+struct UiPasteLimit {
+    max_chars: usize,
+    chars: usize,
+    chunks: usize,
+    /// 1-based number of the chunk `PasteNextChunk` pastes next.
+    next_chunk: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct UiPipelineTrace {
+    rule_id: String,
+    steps: Vec<UiTraceStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct UiTraceStep {
+    transform: &'static str,
+    /// Output of this step, or its input when it failed.
+    output: String,
+    micros: u128,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct UiSession {
+    time: String,
+    rule: String,
+    snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct UiHealthIssue {
+    component: &'static str,
+    problem: String,
+    fix: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct UiInterrupted {
+    message: String,
+    can_restore: bool,
 }
 
 #[derive(Debug, Serialize)]
 struct UiConfigState {
     hotkey_combo: String,
+    /// `hotkey_combo` in platform glyphs, e.g. `⌘⇧V`.
+    hotkey_label: String,
     hotkey_apps: Vec<UiHotkeyApp>,
     rules: Vec<UiRuleConfig>,
+    presets: Vec<UiPreset>,
+    plugins: Vec<UiPlugin>,
     hotkey_warnings: Vec<String>,
 }
 
@@ -120,12 +328,30 @@ struct UiStats {
     after_lines: usize,
     diff_added: usize,
     diff_removed: usize,
+    /// Rough token count of the input, shown before sending it to a remote model.
+    estimated_tokens: usize,
+    before_whitespace: UiWhitespaceStats,
+    after_whitespace: UiWhitespaceStats,
+}
+
+/// Whitespace traits that commonly break a paste in the destination.
+#[derive(Debug, Serialize)]
+struct UiWhitespaceStats {
+    /// `"lf"`, `"crlf"`, `"cr"`, `"mixed"`, or `"none"` for single-line text.
+    line_endings: &'static str,
+    /// `"tabs"`, `"spaces"`, `"mixed"`, or `"none"` when no line is indented.
+    indentation: &'static str,
+    /// Lines ending in spaces or tabs.
+    trailing_whitespace: usize,
+    /// Longest line, in characters.
+    widest_line: usize,
 }
 
 #[derive(Debug, Serialize)]
 struct UiHotkeyApp {
     app: String,
     combo: String,
+    label: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -136,18 +362,48 @@ struct UiRuleConfig {
     pinned: bool,
 }
 
+#[derive(Debug, Serialize)]
+struct UiPreset {
+    id: String,
+    name: String,
+    description: String,
+    installed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct UiPlugin {
+    name: String,
+    /// A rule already runs this plugin.
+    installed: bool,
+}
+
 #[derive(Debug, Serialize)]
 struct UiHistoryItem {
     time: String,
     action: String,
     rule: String,
     snippet: String,
+    /// Consecutive identical actions collapsed into this entry.
+    count: u32,
+    /// Whether the full output is kept, so the entry can be merged.
+    mergeable: bool,
+    policy: Option<String>,
 }
 
 struct TrayHandle {
-    _tray: TrayIcon,
+    tray: TrayIcon,
     show_id: MenuId,
     quit_id: MenuId,
+    auto_accept: CheckMenuItem,
+    track_active_app: CheckMenuItem,
+}
+
+impl TrayHandle {
+    /// Match the check marks to config, which the panel can also change.
+    fn sync(&self, cfg: &config::Config) {
+        self.auto_accept.set_checked(cfg.ui.auto_accept);
+        self.track_active_app.set_checked(cfg.ui.track_active_app);
+    }
 }
 
 #[derive(Debug, Default)]
@@ -166,36 +422,77 @@ struct HotkeyEntry {
     combos: Vec<String>,
 }
 
-struct HistoryItem {
-    time: DateTime<Utc>,
-    action: String,
-    rule: String,
-    snippet: String,
-}
-
 #[derive(Debug)]
 enum UserEvent {
     Ipc(IpcMessage),
     Menu(MenuEvent),
     Hotkey(u32),
+    Api(ApiCall),
+    ActiveApp {
+        name: String,
+        pid: u64,
+        id: Option<String>,
+        url: Option<String>,
+    },
+    /// `assets/panel.html` changed on disk (dev mode).
+    PanelChanged,
 }
 
 type AppResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
 
+/// Stands in for the output of a sensitive rule after it has been used.
+const MASKED_TEXT: &str = "•••••• (hidden: sensitive rule)";
+const ACTIVE_APP_POLL: std::time::Duration = std::time::Duration::from_millis(500);
+const PANEL_SOURCE_POLL: std::time::Duration = std::time::Duration::from_millis(300);
+/// Panel source read by `--dev` instead of the compiled-in copy.
+const PANEL_SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/panel.html");
+
+/// Command-line options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions {
+    /// Load the panel from `assets/panel.html` on disk, reload it when the file
+    /// changes, and enable the web inspector.
+    pub dev: bool,
+}
+
 struct Pasteflow {
     state: AppState,
     window: Option<Arc<Window>>,
     webview: Option<WebView>,
     tray: Option<TrayHandle>,
     proxy: EventLoopProxy<UserEvent>,
+    options: RunOptions,
+    /// Stop flag of the thread following the frontmost app; `None` while the
+    /// panel is hidden or `ui.track_active_app` is off.
+    app_tracking: Option<Arc<AtomicBool>>,
 }
 
 impl Pasteflow {
-    fn new(proxy: EventLoopProxy<UserEvent>) -> AppResult<Self> {
-        let cfg = config::load_or_init()?;
-        let clipboard =
-            Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
-        let hotkey_manager = GlobalHotKeyManager::new()?;
+    fn new(proxy: EventLoopProxy<UserEvent>, options: RunOptions) -> AppResult<Self> {
+        let (mut cfg, invalid) = config::load_or_init()?;
+        if cfg.prune_ui_state(Utc::now()) {
+            let _ = config::save(&cfg);
+        }
+        // Missing pieces put the app in degraded mode instead of stopping it.
+        let last_session = cfg.ui.restore_session.then(session::load).flatten();
+        let mut health = Vec::new();
+        let clipboard = match Clipboard::new() {
+            Ok(clipboard) => Some(clipboard),
+            Err(e) => {
+                health.push(clipboard_issue(e.to_string()));
+                None
+            }
+        };
+        let hotkey_manager = match GlobalHotKeyManager::new() {
+            Ok(manager) => Some(manager),
+            Err(e) => {
+                health.push(hotkey_issue(e.to_string()));
+                None
+            }
+        };
+        for issue in &health {
+            eprintln!("{} unavailable: {}", issue.component, issue.problem);
+        }
 
         let state = AppState {
             cfg,
@@ -208,12 +505,18 @@ impl Pasteflow {
                 diff: String::new(),
                 error: None,
                 active_app: None,
+                active_pid: None,
+                active_app_id: None,
+                active_url: None,
                 content_types: Vec::new(),
                 active_app_key: "global".to_string(),
                 search_query: None,
+                image: None,
+                binary: None,
+                clipboard_hash: None,
             },
             config_text: None,
-            config_error: None,
+            config_error: invalid.map(|err| err.to_string()),
             config_draft_error: None,
             config_diff: None,
             hotkey_manager,
@@ -221,7 +524,20 @@ impl Pasteflow {
             hotkey_map: HashMap::new(),
             hotkey_warnings: Vec::new(),
             history: Vec::new(),
+            metrics: Metrics::default(),
+            interrupted: journal::load_interrupted(),
+            regex_test: None,
+            pipeline_trace: None,
+            policy: None,
+            next_chunk: 0,
+            masked_hash: None,
+            health,
+            last_session,
+            done_hash: None,
         };
+        if let Some(entry) = &state.interrupted {
+            eprintln!("Previous {} did not complete", entry.action.to_lowercase());
+        }
 
         Ok(Self {
             state,
@@ -229,8 +545,41 @@ impl Pasteflow {
             webview: None,
             tray: None,
             proxy,
+            options,
+            app_tracking: None,
         })
     }
+
+    /// Follow the frontmost app only while the panel is up and tracking is on.
+    fn sync_app_tracking(&mut self) {
+        let visible = self
+            .window
+            .as_ref()
+            .is_some_and(|window| window.is_visible().unwrap_or(false));
+        let wanted = visible && self.state.cfg.ui.track_active_app;
+        match (&self.app_tracking, wanted) {
+            (None, true) => {
+                self.app_tracking = Some(spawn_app_tracking(self.proxy.clone()));
+            }
+            (Some(stop), false) => {
+                stop.store(true, Ordering::Relaxed);
+                self.app_tracking = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn refresh_tray_icon(&self, theme: Theme) {
+        let (Some(window), Some(tray)) = (&self.window, &self.tray) else {
+            return;
+        };
+        match load_icon(window.scale_factor(), theme) {
+            Ok(icon) => {
+                let _ = tray.tray.set_icon(Some(icon));
+            }
+            Err(e) => eprintln!("Failed to update tray icon: {}", e),
+        }
+    }
 }
 
 impl ApplicationHandler<UserEvent> for Pasteflow {
@@ -252,10 +601,15 @@ impl ApplicationHandler<UserEvent> for Pasteflow {
 
         // Create webview as child to avoid winit contentView replacement panic
         // See: https://github.com/tauri-apps/wry/issues/1477
-        let html = include_str!("../assets/panel.html");
+        let html = if self.options.dev {
+            panel_html_from_disk()
+        } else {
+            include_str!("../assets/panel.html").to_string()
+        };
         let proxy = self.proxy.clone();
         let webview = match WebViewBuilder::new()
             .with_html(html)
+            .with_devtools(self.options.dev)
             .with_ipc_handler(move |req: Request<String>| {
                 if let Ok(event) = serde_json::from_str::<IpcMessage>(req.body()) {
                     let _ = proxy.send_event(UserEvent::Ipc(event));
@@ -276,17 +630,27 @@ impl ApplicationHandler<UserEvent> for Pasteflow {
         };
 
         // Create tray
-        let tray = match build_tray() {
-            Ok(t) => t,
+        let theme = window.theme().unwrap_or(Theme::Light);
+        let tray = match build_tray(window.scale_factor(), theme, &self.state.cfg) {
+            Ok(t) => Some(t),
             Err(e) => {
                 eprintln!("Failed to create tray: {}", e);
-                event_loop.exit();
-                return;
+                self.state.health.push(UiHealthIssue {
+                    component: "Menu bar icon",
+                    problem: e.to_string(),
+                    fix: "Open the panel with the hotkey instead; restart Pasteflow to retry.",
+                });
+                None
             }
         };
 
         // Apply hotkeys
-        let _ = apply_hotkeys(&mut self.state);
+        let hotkeys_ok = apply_hotkeys(&mut self.state).is_ok();
+
+        // With neither a tray nor a hotkey, the panel is the only way to learn why.
+        if (tray.is_none() && !hotkeys_ok) || self.state.last_session.is_some() {
+            open_panel(&mut self.state, &window, &webview);
+        }
 
         // Start hotkey listener thread
         let hotkey_proxy = self.proxy.clone();
@@ -297,6 +661,42 @@ impl ApplicationHandler<UserEvent> for Pasteflow {
             }
         });
 
+        // Reload the panel when its source changes (dev mode)
+        if self.options.dev {
+            let panel_proxy = self.proxy.clone();
+            std::thread::spawn(move || {
+                let modified = || {
+                    std::fs::metadata(PANEL_SOURCE)
+                        .and_then(|m| m.modified())
+                        .ok()
+                };
+                let mut last = modified();
+                loop {
+                    std::thread::sleep(PANEL_SOURCE_POLL);
+                    let current = modified();
+                    if current == last {
+                        continue;
+                    }
+                    last = current;
+                    if panel_proxy.send_event(UserEvent::PanelChanged).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        // Start local API server (opt-in)
+        if self.state.cfg.api.enabled {
+            let api_proxy = self.proxy.clone();
+            let bind = self.state.cfg.api.bind.clone();
+            let token = self.state.cfg.api.token.clone().unwrap_or_default();
+            if let Err(e) = api::spawn(&bind, &token, move |call| {
+                api_proxy.send_event(UserEvent::Api(call)).is_ok()
+            }) {
+                eprintln!("Failed to start API on {}: {}", bind, e);
+            }
+        }
+
         // Start menu event listener thread
         let menu_proxy = self.proxy.clone();
         std::thread::spawn(move || {
@@ -308,7 +708,17 @@ impl ApplicationHandler<UserEvent> for Pasteflow {
 
         self.window = Some(window);
         self.webview = Some(webview);
-        self.tray = Some(tray);
+        self.tray = tray;
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        save_session(&self.state);
+    }
+
+    /// Showing or hiding the panel and toggling tracking all happen in event
+    /// handlers, so checking once they have run keeps the poller in step.
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        self.sync_app_tracking();
     }
 
     fn window_event(
@@ -326,6 +736,13 @@ impl ApplicationHandler<UserEvent> for Pasteflow {
             WindowEvent::Destroyed => {
                 event_loop.exit();
             }
+            WindowEvent::ThemeChanged(theme) => {
+                self.refresh_tray_icon(theme);
+            }
+            WindowEvent::ScaleFactorChanged { .. } => {
+                let theme = self.window.as_ref().and_then(|w| w.theme());
+                self.refresh_tray_icon(theme.unwrap_or(Theme::Light));
+            }
             WindowEvent::Resized(size) => {
                 // Resize webview to match window size
                 if let Some(webview) = &self.webview {
@@ -347,29 +764,96 @@ impl ApplicationHandler<UserEvent> for Pasteflow {
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
         let Some(window) = &self.window else { return };
         let Some(webview) = &self.webview else { return };
-        let Some(tray) = &self.tray else { return };
 
         match event {
             UserEvent::Ipc(msg) => {
                 handle_ipc(&mut self.state, msg, window, webview);
+                if let Some(tray) = &self.tray {
+                    tray.sync(&self.state.cfg);
+                }
             }
             UserEvent::Menu(event) => {
+                let Some(tray) = &self.tray else { return };
                 if event.id == tray.show_id {
                     open_panel(&mut self.state, window, webview);
                 } else if event.id == tray.quit_id {
                     event_loop.exit();
+                } else if &event.id == tray.auto_accept.id() {
+                    self.state.cfg.ui.auto_accept = !self.state.cfg.ui.auto_accept;
+                    persist_config(&mut self.state);
+                    tray.sync(&self.state.cfg);
+                    send_state(&self.state, webview);
+                } else if &event.id == tray.track_active_app.id() {
+                    self.state.cfg.ui.track_active_app = !self.state.cfg.ui.track_active_app;
+                    persist_config(&mut self.state);
+                    tray.sync(&self.state.cfg);
+                    send_state(&self.state, webview);
                 }
             }
             UserEvent::Hotkey(id) => {
-                if should_handle_hotkey(&self.state, id) {
+                let visible = window.is_visible().unwrap_or(false);
+                // While the panel is up it is frontmost; judge app-specific hotkeys by
+                // the app it was opened over.
+                let (active_app, active_app_id) = if visible {
+                    (
+                        self.state.panel.active_app.clone(),
+                        self.state.panel.active_app_id.clone(),
+                    )
+                } else {
+                    active_app_name_and_id()
+                };
+                if !should_handle_hotkey(
+                    &self.state,
+                    id,
+                    active_app.as_deref(),
+                    active_app_id.as_deref(),
+                ) {
+                    return;
+                }
+                if visible && clipboard_unchanged(&mut self.state) {
+                    window.set_visible(false);
+                } else {
                     open_panel(&mut self.state, window, webview);
                 }
             }
+            UserEvent::Api(call) => {
+                let _ = call.reply.send(handle_api(&self.state, call.request));
+            }
+            UserEvent::ActiveApp { name, pid, id, url } => {
+                if self.state.cfg.ui.track_active_app
+                    && window.is_visible().unwrap_or(false)
+                    && (self.state.panel.active_app.as_deref() != Some(name.as_str())
+                        || self.state.panel.active_url != url)
+                {
+                    self.state.panel.active_app_key = name.clone();
+                    self.state.panel.active_app = Some(name);
+                    self.state.panel.active_pid = Some(pid);
+                    self.state.panel.active_app_id = id;
+                    self.state.panel.active_url = url;
+                    // Re-running the rule could block on a `command` transform
+                    // and would drop a chunked paste, so only do it when the
+                    // selection changed; the new app still gets its policies.
+                    let selected = self.state.selected_rule_id.clone();
+                    rebuild_suggestions(&mut self.state);
+                    if self.state.selected_rule_id != selected {
+                        refresh_preview(&mut self.state);
+                    } else {
+                        refresh_policy(&mut self.state);
+                    }
+                    send_state(&self.state, webview);
+                }
+            }
+            UserEvent::PanelChanged => {
+                // The reloaded page posts `ready` and receives fresh state.
+                if let Err(err) = webview.load_html(&panel_html_from_disk()) {
+                    eprintln!("Failed to reload panel: {}", err);
+                }
+            }
         }
     }
 }
 
-pub fn run() -> AppResult<()> {
+pub fn run(options: RunOptions) -> AppResult<()> {
     // On macOS, set activation policy to accessory (no dock icon)
     #[cfg(target_os = "macos")]
     {
@@ -387,7 +871,7 @@ pub fn run() -> AppResult<()> {
         .map_err(|e| format!("Failed to create event loop: {}", e))?;
 
     let proxy = event_loop.create_proxy();
-    let mut app = Pasteflow::new(proxy)?;
+    let mut app = Pasteflow::new(proxy, options)?;
 
     event_loop
         .run_app(&mut app)
@@ -396,27 +880,47 @@ pub fn run() -> AppResult<()> {
     Ok(())
 }
 
-fn build_tray() -> AppResult<TrayHandle> {
+/// Read the panel source for dev mode, keeping the compiled-in copy if it's missing.
+fn panel_html_from_disk() -> String {
+    std::fs::read_to_string(PANEL_SOURCE).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", PANEL_SOURCE, err);
+        include_str!("../assets/panel.html").to_string()
+    })
+}
+
+fn build_tray(scale_factor: f64, theme: Theme, cfg: &config::Config) -> AppResult<TrayHandle> {
     let menu = Menu::new();
     let show_item = MenuItem::new("Show Pasteflow", true, None);
     let quit_item = MenuItem::new("Quit", true, None);
     let show_id = show_item.id().clone();
     let quit_id = quit_item.id().clone();
+    let auto_accept = CheckMenuItem::new("Auto-accept Rules", true, cfg.ui.auto_accept, None);
+    let track_active_app =
+        CheckMenuItem::new("Follow Active App", true, cfg.ui.track_active_app, None);
+    let options = Submenu::new("Options", true);
+    options.append(&auto_accept).map_err(boxed)?;
+    options.append(&track_active_app).map_err(boxed)?;
     menu.append(&show_item).map_err(boxed)?;
+    menu.append(&options).map_err(boxed)?;
+    menu.append(&PredefinedMenuItem::separator())
+        .map_err(boxed)?;
     menu.append(&quit_item).map_err(boxed)?;
 
-    let icon = load_icon()?;
+    let icon = load_icon(scale_factor, theme)?;
     let tray = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
         .with_tooltip("Pasteflow")
         .with_icon(icon)
+        .with_icon_as_template(cfg!(target_os = "macos"))
         .build()
         .map_err(boxed)?;
 
     Ok(TrayHandle {
-        _tray: tray,
+        tray,
         show_id,
         quit_id,
+        auto_accept,
+        track_active_app,
     })
 }
 
@@ -424,34 +928,110 @@ fn boxed<E: Error + Send + Sync + 'static>(err: E) -> Box<dyn Error + Send + Syn
     Box::new(err)
 }
 
-fn load_icon() -> Result<tray_icon::Icon, Box<dyn Error + Send + Sync>> {
-    let bytes = include_bytes!("../assets/icon.png");
-    let image =
-        image::load_from_memory(bytes).map_err(|e| format!("Failed to decode icon: {}", e))?;
-    let rgba = image.to_rgba8();
-    let (width, height) = rgba.dimensions();
-    tray_icon::Icon::from_rgba(rgba.into_raw(), width, height)
+/// Logical height of a menu bar icon.
+const TRAY_ICON_POINTS: f64 = 18.0;
+
+/// Tray glyphs rasterized by `build.rs`: pixel size, black glyph, white glyph.
+const TRAY_ICONS: [(u32, &[u8], &[u8]); 3] = [
+    (
+        18,
+        include_bytes!(concat!(env!("OUT_DIR"), "/tray-18-black.rgba")),
+        include_bytes!(concat!(env!("OUT_DIR"), "/tray-18-white.rgba")),
+    ),
+    (
+        36,
+        include_bytes!(concat!(env!("OUT_DIR"), "/tray-36-black.rgba")),
+        include_bytes!(concat!(env!("OUT_DIR"), "/tray-36-white.rgba")),
+    ),
+    (
+        54,
+        include_bytes!(concat!(env!("OUT_DIR"), "/tray-54-black.rgba")),
+        include_bytes!(concat!(env!("OUT_DIR"), "/tray-54-white.rgba")),
+    ),
+];
+
+/// Pick the smallest glyph that covers the display's scale factor. macOS tints
+/// template icons for the menu bar itself, so it always gets the black glyph.
+fn load_icon(
+    scale_factor: f64,
+    theme: Theme,
+) -> Result<tray_icon::Icon, Box<dyn Error + Send + Sync>> {
+    let wanted = (TRAY_ICON_POINTS * scale_factor).round() as u32;
+    let (size, black, white) = TRAY_ICONS
+        .iter()
+        .find(|(size, _, _)| *size >= wanted)
+        .unwrap_or(&TRAY_ICONS[TRAY_ICONS.len() - 1]);
+    let rgba = if cfg!(target_os = "macos") || theme == Theme::Light {
+        black
+    } else {
+        white
+    };
+    tray_icon::Icon::from_rgba(rgba.to_vec(), *size, *size)
         .map_err(|e| format!("Failed to create icon: {}", e).into())
 }
 
 fn open_panel(state: &mut AppState, window: &Window, webview: &WebView) {
-    let text = match state.clipboard.get_text() {
-        Ok(t) => t,
-        Err(_) => {
-            state.panel.error = Some("Failed to read clipboard".to_string());
+    state.panel.image = None;
+    state.panel.binary = None;
+    let current = clipboard(state)
+        .map(|clipboard| clipboard.get_text().map_err(|_| read_non_text(clipboard)));
+    // Reopening with our last chunk still on the clipboard continues the chunks.
+    if state.next_chunk > 0
+        && let Some(Ok(text)) = &current
+        && state.panel.clipboard_hash.as_deref() == Some(history::content_hash(text).as_str())
+    {
+        show_panel(state, window, webview);
+        return;
+    }
+    state.next_chunk = 0;
+    state.panel.clipboard_hash = None;
+    let text = match current {
+        Some(Ok(t)) => {
+            state.panel.clipboard_hash = Some(history::content_hash(&t));
+            t
+        }
+        Some(Err((image, binary))) => {
+            if image.is_none() && binary.is_none() {
+                state.panel.error = Some("Failed to read clipboard".to_string());
+            }
+            state.panel.image = image;
+            state.panel.binary = binary;
+            String::new()
+        }
+        None => {
+            state.panel.error = Some("Clipboard is unavailable".to_string());
             String::new()
         }
     };
-    let content_types = detect::detect_content_types(&text);
-    let active_app = active_app_name();
+    let content_types = content_types_for(&state.cfg, &text, non_text_type(&state.panel));
+    // Refreshing an open panel keeps the app it was opened over.
+    let (active_app, active_pid, active_app_id) = if window.is_visible().unwrap_or(false) {
+        (
+            state.panel.active_app.clone(),
+            state.panel.active_pid,
+            state.panel.active_app_id.clone(),
+        )
+    } else {
+        match active_win_pos_rs::get_active_window() {
+            Ok(window) => (
+                Some(window.app_name),
+                Some(window.process_id),
+                app_identifier(&window.process_path),
+            ),
+            Err(_) => (None, None, None),
+        }
+    };
+    // Read fresh even on a refresh, since the tab may have changed.
+    let active_url = active_app_id.as_deref().and_then(browser::active_tab_url);
     let app_key = active_app.clone().unwrap_or_else(|| "global".to_string());
     let ctx = MatchContext {
         text: text.clone(),
         content_types: content_types.clone(),
         active_app: active_app.clone(),
+        active_app_id: active_app_id.clone(),
+        active_url: active_url.clone(),
     };
-    state.suggestions =
-        crate::rules::suggest_rules(&state.cfg.rules, &ctx, state.cfg.ui.suggestions);
+    state.suggestions = compute_suggestions(&state.cfg, &ctx);
     state.selected_rule_id = state
         .cfg
         .ui_state
@@ -465,8 +1045,14 @@ fn open_panel(state: &mut AppState, window: &Window, webview: &WebView) {
     }
     state.panel.input = text;
     state.panel.active_app = active_app;
+    state.panel.active_pid = active_pid;
+    state.panel.active_app_id = active_app_id;
+    state.panel.active_url = active_url;
     state.panel.content_types = content_types;
     state.panel.active_app_key = app_key.clone();
+    if let Some(prefs) = state.cfg.ui_state.get_mut(&app_key) {
+        prefs.last_used = Some(Utc::now());
+    }
     state.panel.search_query = state
         .cfg
         .ui_state
@@ -485,11 +1071,19 @@ fn open_panel(state: &mut AppState, window: &Window, webview: &WebView) {
 
     if let Some(rule) = selected_rule(state)
         && rule.auto_accept
+        && state.cfg.ui.auto_accept
+        // Policy warnings and blocks need to be seen, so show the panel.
+        && state.policy.is_none()
+        // When nothing was pasted, fall through so the panel shows why.
+        && apply_paste(state)
     {
-        apply_paste(state);
         return;
     }
 
+    show_panel(state, window, webview);
+}
+
+fn show_panel(state: &AppState, window: &Window, webview: &WebView) {
     send_state(state, webview);
     window.set_visible(true);
     window.request_user_attention(Some(winit::window::UserAttentionType::Informational));
@@ -499,16 +1093,107 @@ fn open_panel(state: &mut AppState, window: &Window, webview: &WebView) {
 fn handle_ipc(state: &mut AppState, msg: IpcMessage, window: &Window, webview: &WebView) {
     match msg {
         IpcMessage::Paste => {
-            apply_paste(state);
-            window.set_visible(false);
+            if apply_paste(state) {
+                window.set_visible(false);
+            } else {
+                send_state(state, webview);
+            }
         }
         IpcMessage::Copy => {
-            apply_copy(state);
-            window.set_visible(false);
+            if apply_copy(state) {
+                window.set_visible(false);
+            } else {
+                send_state(state, webview);
+            }
+        }
+        IpcMessage::CopyDiff { markdown } => {
+            if selected_rule(state).is_some_and(Rule::is_sensitive) {
+                state.panel.error = Some("Diffs of sensitive rules are not copied.".to_string());
+            } else if state.panel.diff.is_empty() {
+                state.panel.error = Some("No changes to copy.".to_string());
+            } else {
+                let file_name = format!("clipboard.{}", clip_extension(&state.panel.content_types));
+                let text = diff::export_diff(
+                    &state.panel.input,
+                    &state.panel.output,
+                    &file_name,
+                    markdown,
+                );
+                match set_clipboard_text(state, text) {
+                    Ok(()) => {
+                        window.set_visible(false);
+                        return;
+                    }
+                    Err(e) => state.panel.error = Some(format!("Failed to copy diff: {}", e)),
+                }
+            }
+            send_state(state, webview);
         }
         IpcMessage::Cancel => {
             window.set_visible(false);
         }
+        IpcMessage::Ready => {
+            send_state(state, webview);
+        }
+        IpcMessage::RestoreInterrupted => {
+            let previous = state.interrupted.take().and_then(|entry| entry.previous);
+            if let Some(previous) = previous
+                && let Err(e) = set_clipboard_text(state, previous)
+            {
+                state.panel.error = Some(format!("Failed to restore clipboard: {}", e));
+            }
+            let _ = journal::clear();
+            send_state(state, webview);
+        }
+        IpcMessage::TestRegex { pattern, sample } => {
+            state.regex_test = if pattern.is_empty() {
+                None
+            } else {
+                let sample = sample.as_deref().unwrap_or(&state.panel.input);
+                Some(rules::test_regex(&pattern, sample))
+            };
+            send_state(state, webview);
+        }
+        IpcMessage::PasteNextChunk => {
+            if paste_next_chunk(state) {
+                window.set_visible(false);
+            } else {
+                send_state(state, webview);
+            }
+        }
+        IpcMessage::RequestPipelineTrace => {
+            state.pipeline_trace =
+                selected_rule(state).map(|rule| trace_rule(rule, &state.panel.input));
+            send_state(state, webview);
+        }
+        IpcMessage::RestoreSession => {
+            if let Some(saved) = state.last_session.take() {
+                restore_session(state, saved);
+            }
+            let _ = session::clear();
+            send_state(state, webview);
+        }
+        IpcMessage::MergeHistory {
+            indexes,
+            separator,
+            rule,
+        } => {
+            match history::merge(&state.history, &indexes, &separator) {
+                Ok(merged) => load_panel_text(state, merged, rule),
+                Err(err) => state.panel.error = Some(err),
+            }
+            send_state(state, webview);
+        }
+        IpcMessage::DismissSession => {
+            state.last_session = None;
+            let _ = session::clear();
+            send_state(state, webview);
+        }
+        IpcMessage::DismissInterrupted => {
+            state.interrupted = None;
+            let _ = journal::clear();
+            send_state(state, webview);
+        }
         IpcMessage::SelectRule { id } => {
             state.selected_rule_id = Some(id);
             update_ui_prefs(state, None, state.selected_rule_id.clone());
@@ -544,10 +1229,27 @@ fn handle_ipc(state: &mut AppState, msg: IpcMessage, window: &Window, webview: &
             }
             send_state(state, webview);
         }
+        IpcMessage::RenameRule { old_id, new_id } => {
+            let new_id = new_id.trim().to_string();
+            match state.cfg.rename_rule(&old_id, &new_id) {
+                Ok(()) => {
+                    if state.selected_rule_id.as_deref() == Some(old_id.as_str()) {
+                        state.selected_rule_id = Some(new_id);
+                    }
+                    persist_config(state);
+                    rebuild_suggestions(state);
+                    refresh_preview(state);
+                }
+                Err(err) => {
+                    state.config_error = Some(err.to_string());
+                }
+            }
+            send_state(state, webview);
+        }
         IpcMessage::UpdateHotkeyCombo { combo } => {
             let trimmed = combo.trim().to_string();
             if !trimmed.is_empty() {
-                state.cfg.hotkey.combo = trimmed;
+                state.cfg.hotkey.combo = canonical_combo(&trimmed).unwrap_or(trimmed);
                 persist_config(state);
                 let _ = apply_hotkeys(state);
             }
@@ -560,7 +1262,8 @@ fn handle_ipc(state: &mut AppState, msg: IpcMessage, window: &Window, webview: &
                 if combo_trimmed.is_empty() {
                     state.cfg.hotkey.apps.remove(&app_trimmed);
                 } else {
-                    state.cfg.hotkey.apps.insert(app_trimmed, combo_trimmed);
+                    let combo = canonical_combo(&combo_trimmed).unwrap_or(combo_trimmed);
+                    state.cfg.hotkey.apps.insert(app_trimmed, combo);
                 }
                 persist_config(state);
                 let _ = apply_hotkeys(state);
@@ -577,6 +1280,34 @@ fn handle_ipc(state: &mut AppState, msg: IpcMessage, window: &Window, webview: &
             update_ui_prefs(state, Some(value), None);
             send_state(state, webview);
         }
+        IpcMessage::InstallPreset { id } => {
+            if let Some(preset) = catalog::find(&id)
+                && state.cfg.rules.iter().all(|rule| rule.id != preset.id)
+            {
+                state.cfg.rules.push(preset.to_rule());
+                persist_config(state);
+                rebuild_suggestions(state);
+                refresh_preview(state);
+            }
+            send_state(state, webview);
+        }
+        IpcMessage::InstallPlugin { name } => {
+            if plugins::discover().contains(&name)
+                && !state.cfg.rules.iter().any(|rule| runs_plugin(rule, &name))
+            {
+                state.cfg.rules.push(plugin_rule(&state.cfg.rules, &name));
+                persist_config(state);
+                rebuild_suggestions(state);
+                refresh_preview(state);
+            }
+            send_state(state, webview);
+        }
+        IpcMessage::ClearUiState => {
+            state.cfg.ui_state.clear();
+            state.panel.search_query = None;
+            persist_config(state);
+            send_state(state, webview);
+        }
         IpcMessage::RequestConfig => {
             match config::load_raw() {
                 Ok(raw) => {
@@ -637,9 +1368,19 @@ fn handle_ipc(state: &mut AppState, msg: IpcMessage, window: &Window, webview: &
 }
 
 fn refresh_preview(state: &mut AppState) {
+    state.pipeline_trace = None;
+    state.next_chunk = 0;
     let input = state.panel.input.clone();
     let result = if let Some(rule) = selected_rule(state) {
-        apply_rule(rule, &input)
+        let started = std::time::Instant::now();
+        let result = apply_rule(rule, &input, state.panel.image.as_ref());
+        if !rule.is_sensitive() {
+            let rule_id = rule.id.clone();
+            state
+                .metrics
+                .record_run(&rule_id, started.elapsed(), result.is_ok());
+        }
+        result
     } else {
         Ok(input.clone())
     };
@@ -655,18 +1396,33 @@ fn refresh_preview(state: &mut AppState) {
             state.panel.error = Some(err);
         }
     }
+    refresh_policy(state);
+}
+
+/// Check the output against `[[policies]]` for the current destination app.
+fn refresh_policy(state: &mut AppState) {
+    let destination = policy::Destination {
+        name: state.panel.active_app.as_deref(),
+        id: state.panel.active_app_id.as_deref(),
+    };
+    state.policy = policy::evaluate(&state.cfg.policies, &state.panel.output, destination);
 }
 
 fn rebuild_suggestions(state: &mut AppState) {
     let text = state.panel.input.clone();
-    let content_types = detect::detect_content_types(&text);
+    let content_types = content_types_for(&state.cfg, &text, non_text_type(&state.panel));
     let ctx = MatchContext {
         text,
         content_types,
-        active_app: state.panel.active_app.clone().or_else(active_app_name),
+        active_app: state
+            .panel
+            .active_app
+            .clone()
+            .or_else(|| active_app_name_and_id().0),
+        active_app_id: state.panel.active_app_id.clone(),
+        active_url: state.panel.active_url.clone(),
     };
-    state.suggestions =
-        crate::rules::suggest_rules(&state.cfg.rules, &ctx, state.cfg.ui.suggestions);
+    state.suggestions = compute_suggestions(&state.cfg, &ctx);
     if let Some(prefs) = state.cfg.ui_state.get(&state.panel.active_app_key) {
         state.panel.search_query = prefs.search.clone();
     }
@@ -680,16 +1436,111 @@ fn rebuild_suggestions(state: &mut AppState) {
     }
 }
 
-fn apply_rule(rule: &Rule, input: &str) -> Result<String, String> {
-    if let Some(kind) = rule.transform_kind() {
-        match kind.apply(input) {
-            Ok(out) => Ok(out),
-            Err(err) => Err(format!("Transform error: {}", err)),
-        }
-    } else if rule.llm.is_some() {
+/// What the panel shows instead of text, when the clipboard held no text.
+fn non_text_type(panel: &PanelState) -> Option<ContentType> {
+    if panel.image.is_some() {
+        Some(ContentType::Image)
+    } else if panel.binary.is_some() {
+        Some(ContentType::Binary)
+    } else {
+        None
+    }
+}
+
+fn content_types_for(
+    cfg: &config::Config,
+    text: &str,
+    non_text: Option<ContentType>,
+) -> Vec<(ContentType, f32)> {
+    if let Some(content_type) = non_text {
+        return vec![(content_type, 1.0)];
+    }
+    let mut types = detect::detect_content_types_within(text, cfg.ui.parse_limit_bytes);
+    let structured = types
+        .iter()
+        .any(|(t, _)| matches!(t, ContentType::Json | ContentType::Yaml));
+    if !structured
+        && let Some(language) = detect::detect_language(detect::probe(text))
+        && !language.eq_ignore_ascii_case(&cfg.ui.primary_language)
+    {
+        types.push((ContentType::Foreign, 0.8));
+    }
+    if text.chars().count() > cfg.ui.long_text_chars {
+        types.push((ContentType::Long, 1.0));
+    }
+    types.extend(detect::detect_custom(detect::probe(text), &cfg.detectors));
+    types
+}
+
+fn compute_suggestions(cfg: &config::Config, ctx: &MatchContext) -> Vec<Suggestion> {
+    let suggestions = crate::rules::suggest_rules(&cfg.rules, ctx, cfg.ui.suggestions);
+    if !suggestions.is_empty() {
+        return suggestions;
+    }
+    crate::rules::fallback_rules(
+        &cfg.rules,
+        &cfg.ui.fallback_rules,
+        &ctx.text,
+        cfg.ui.suggestions,
+    )
+}
+
+fn apply_rule(rule: &Rule, input: &str, image: Option<&ClipboardImage>) -> Result<String, String> {
+    if rule.uses_remote() {
         Err("LLM rule is configured but not enabled in this MVP.".to_string())
+    } else if let ([TransformKind::ImageEmbed { format, dir, .. }], Some(image)) =
+        (rule.transforms(), image)
+    {
+        Ok(image.preview(dir, *format))
     } else {
-        Ok(input.to_string())
+        let stages = rule.transforms();
+        let mut output = input.to_string();
+        for (index, kind) in stages.iter().enumerate() {
+            output = kind.apply(&output).map_err(|err| {
+                if stages.len() == 1 {
+                    format!("Transform error: {}", err)
+                } else {
+                    format!(
+                        "Transform error in step {} of {} ({}): {}",
+                        index + 1,
+                        stages.len(),
+                        transform_label(kind),
+                        err
+                    )
+                }
+            })?;
+        }
+        Ok(output)
+    }
+}
+
+/// Apply `rule`'s transforms one by one, stopping at the first failure.
+fn trace_rule(rule: &Rule, input: &str) -> UiPipelineTrace {
+    let mut steps = Vec::new();
+    let mut current = input.to_string();
+    for kind in rule.transforms() {
+        let started = std::time::Instant::now();
+        let result = kind.apply(&current);
+        let micros = started.elapsed().as_micros();
+        let (output, error) = match result {
+            Ok(output) => (output, None),
+            Err(err) => (current.clone(), Some(err.to_string())),
+        };
+        let failed = error.is_some();
+        steps.push(UiTraceStep {
+            transform: transform_label(kind),
+            output: output.clone(),
+            micros,
+            error,
+        });
+        if failed {
+            break;
+        }
+        current = output;
+    }
+    UiPipelineTrace {
+        rule_id: rule.id.clone(),
+        steps,
     }
 }
 
@@ -706,12 +1557,62 @@ fn selected_rule(state: &AppState) -> Option<&Rule> {
     state.cfg.rules.iter().find(|rule| rule.id == id)
 }
 
+fn handle_api(state: &AppState, request: ApiRequest) -> ApiResponse {
+    match request {
+        ApiRequest::GetState => match serde_json::to_string(&build_api_state(state)) {
+            Ok(payload) => ApiResponse::json(payload),
+            Err(err) => {
+                ApiResponse::json(serde_json::json!({ "error": err.to_string() }).to_string())
+            }
+        },
+        ApiRequest::Metrics => ApiResponse::prometheus(state.metrics.to_prometheus()),
+        ApiRequest::MetricsJson => ApiResponse::json(state.metrics.to_json().to_string()),
+    }
+}
+
+fn build_api_state(state: &AppState) -> ApiState {
+    let sensitive = selected_rule(state).is_some_and(Rule::is_sensitive);
+    ApiState {
+        active_app: state.panel.active_app.clone(),
+        content_types: state
+            .panel
+            .content_types
+            .iter()
+            .map(|(content_type, _)| content_type_label(content_type))
+            .collect(),
+        selected_rule_id: state.selected_rule_id.clone(),
+        suggestions: state
+            .suggestions
+            .iter()
+            .map(|suggestion| ApiRule {
+                id: suggestion.rule.id.clone(),
+                name: suggestion.rule.name.clone(),
+                score: suggestion.score,
+            })
+            .collect(),
+        stats: (!sensitive).then(|| compute_stats(&state.panel)),
+        error: state.panel.error.clone().filter(|_| !sensitive),
+        policy: state.policy.as_ref().map(|decision| UiPolicy {
+            action: decision.action,
+            reasons: decision.reasons.clone(),
+        }),
+        health: state.health.clone(),
+    }
+}
+
 fn send_state(state: &AppState, webview: &WebView) {
+    if let Ok(payload) = serde_json::to_string(&build_ui_state(state)) {
+        let script = format!("window.__SET_STATE__({});", payload);
+        let _ = webview.evaluate_script(&script);
+    }
+}
+
+fn build_ui_state(state: &AppState) -> UiState {
     let ctx = current_match_context(state);
     let suggestions: Vec<UiRule> = state
         .suggestions
         .iter()
-        .map(|suggestion| ui_rule_with_score(&suggestion.rule, suggestion.score))
+        .map(|suggestion| ui_rule_with_score(&suggestion.rule, suggestion.score, &ctx))
         .collect();
 
     let all_rules: Vec<UiRule> = state
@@ -720,7 +1621,7 @@ fn send_state(state: &AppState, webview: &WebView) {
         .iter()
         .map(|rule| {
             let score = rule_score(rule, &ctx);
-            ui_rule_with_score(rule, score)
+            ui_rule_with_score(rule, score, &ctx)
         })
         .collect();
 
@@ -728,18 +1629,37 @@ fn send_state(state: &AppState, webview: &WebView) {
         .panel
         .content_types
         .iter()
-        .map(content_type_label)
+        .map(|(content_type, _)| content_type_label(content_type))
         .collect();
 
-    let ui_state = UiState {
-        before: state.panel.input.clone(),
-        after: state.panel.output.clone(),
-        diff: state.panel.diff.clone(),
+    // Once a sensitive output has been used, hide it wherever it shows up again.
+    let masked = |text: &str| {
+        !text.is_empty()
+            && state
+                .masked_hash
+                .as_deref()
+                .is_some_and(|hash| history::content_hash(text) == hash)
+    };
+    let mask = masked(&state.panel.input) || masked(&state.panel.output);
+    let shown = |text: &String| {
+        if mask {
+            MASKED_TEXT.to_string()
+        } else {
+            text.clone()
+        }
+    };
+
+    UiState {
+        before: shown(&state.panel.input),
+        after: shown(&state.panel.output),
+        diff: shown(&state.panel.diff),
         suggestions,
         all_rules,
         selected_rule_id: state.selected_rule_id.clone(),
         active_app: state.panel.active_app.clone(),
         content_types,
+        image: state.panel.image.as_ref().map(ClipboardImage::describe),
+        binary: state.panel.binary.clone(),
         search_query: state.panel.search_query.clone(),
         config: {
             let mut cfg = build_ui_config_state(&state.cfg);
@@ -753,11 +1673,44 @@ fn send_state(state: &AppState, webview: &WebView) {
         history: state.history.iter().map(ui_history_item).collect(),
         stats: compute_stats(&state.panel),
         error: state.panel.error.clone(),
-    };
-
-    if let Ok(payload) = serde_json::to_string(&ui_state) {
-        let script = format!("window.__SET_STATE__({});", payload);
-        let _ = webview.evaluate_script(&script);
+        interrupted: state.interrupted.as_ref().map(|entry| UiInterrupted {
+            message: entry.describe(Some(&state.panel.input)),
+            can_restore: entry.previous.is_some(),
+        }),
+        regex_test: state
+            .regex_test
+            .as_ref()
+            .and_then(|result| result.as_ref().ok().cloned()),
+        regex_test_error: state
+            .regex_test
+            .as_ref()
+            .and_then(|result| result.as_ref().err().cloned()),
+        pipeline_trace: state.pipeline_trace.clone().filter(|_| !mask),
+        policy: state.policy.as_ref().map(|decision| UiPolicy {
+            action: decision.action,
+            reasons: decision.reasons.clone(),
+        }),
+        paste_limit: paste_limit(state).map(|max_chars| UiPasteLimit {
+            max_chars,
+            chars: state.panel.output.chars().count(),
+            chunks: chunks::split(&state.panel.output, max_chars).len(),
+            next_chunk: state.next_chunk + 1,
+        }),
+        health: state.health.clone(),
+        last_session: state.last_session.as_ref().map(|saved| UiSession {
+            time: saved
+                .saved_at
+                .with_timezone(&Local)
+                .format("%b %-d %H:%M")
+                .to_string(),
+            rule: saved
+                .selected_rule_id
+                .as_deref()
+                .and_then(|id| state.cfg.rules.iter().find(|rule| rule.id == id))
+                .map(|rule| rule.name.clone())
+                .unwrap_or_else(|| "No rule".to_string()),
+            snippet: history::snippet_text(&saved.input),
+        }),
     }
 }
 
@@ -769,9 +1722,10 @@ fn build_ui_config_state(cfg: &config::Config) -> UiConfigState {
         .map(|(app, combo)| UiHotkeyApp {
             app: app.clone(),
             combo: combo.clone(),
+            label: combo_label(combo),
         })
         .collect();
-    hotkey_apps.sort_by(|a, b| a.app.to_lowercase().cmp(&b.app.to_lowercase()));
+    hotkey_apps.sort_by_key(|a| a.app.to_lowercase());
 
     let rules = cfg
         .rules
@@ -784,32 +1738,299 @@ fn build_ui_config_state(cfg: &config::Config) -> UiConfigState {
         })
         .collect();
 
+    let presets = catalog::PRESETS
+        .iter()
+        .map(|preset| UiPreset {
+            id: preset.id.to_string(),
+            name: preset.name.to_string(),
+            description: preset.description.to_string(),
+            installed: cfg.rules.iter().any(|rule| rule.id == preset.id),
+        })
+        .collect();
+
+    let plugins = plugins::discover()
+        .into_iter()
+        .map(|name| UiPlugin {
+            installed: cfg.rules.iter().any(|rule| runs_plugin(rule, &name)),
+            name,
+        })
+        .collect();
+
     UiConfigState {
         hotkey_combo: cfg.hotkey.combo.clone(),
+        hotkey_label: combo_label(&cfg.hotkey.combo),
         hotkey_apps,
         rules,
+        presets,
+        plugins,
         hotkey_warnings: Vec::new(),
     }
 }
 
-fn apply_copy(state: &mut AppState) {
-    apply_copy_internal(state, "Copy");
+fn runs_plugin(rule: &Rule, name: &str) -> bool {
+    matches!(rule.transforms(), [TransformKind::Plugin { name: plugin }] if plugin == name)
+}
+
+/// A rule running plugin `name`, with an id like `plugin_word_count` that no
+/// rule in `rules` uses yet.
+fn plugin_rule(rules: &[Rule], name: &str) -> Rule {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let base = format!("plugin_{}", slug);
+    let mut id = base.clone();
+    let mut n = 2;
+    while rules.iter().any(|rule| rule.id == id) {
+        id = format!("{}_{}", base, n);
+        n += 1;
+    }
+    let mut rule = Rule::new(id, name);
+    rule.description = Some(format!("Runs the {}.wasm plugin.", name));
+    rule.transform = vec![TransformKind::Plugin {
+        name: name.to_string(),
+    }];
+    rule
+}
+
+/// Returns `false`, with `panel.error` set, if nothing was written.
+fn apply_copy(state: &mut AppState) -> bool {
+    apply_copy_internal(state, "Copy")
+}
+
+/// Returns `false`, with `panel.error` set, if nothing was pasted or copied.
+fn apply_paste(state: &mut AppState) -> bool {
+    let mut backend = match paste::backend(state.cfg.paste.backend) {
+        Ok(backend) => backend,
+        Err(err) => {
+            if !apply_copy_internal(state, "Paste") {
+                return false;
+            }
+            eprintln!("Paste failed: {}", err);
+            state.panel.error = Some(err.to_string());
+            return true;
+        }
+    };
+    if !prepare_output(state) || !check_clipboard_unreplaced(state) || !check_policy(state, "Paste")
+    {
+        return false;
+    }
+    let entry = begin_journal(state, "Paste");
+    paste_output(state, backend.as_mut());
+    finish_journal(entry);
+    true
 }
 
-fn apply_paste(state: &mut AppState) {
-    apply_copy_internal(state, "Paste");
-    // Simulate Cmd+V to paste - enigo 0.1 doesn't return errors
-    // Small delays ensure key events are processed in order
-    let mut enigo = Enigo::new();
-    enigo.key_down(Key::Meta);
-    std::thread::sleep(std::time::Duration::from_millis(10));
-    enigo.key_click(Key::Layout('v'));
-    std::thread::sleep(std::time::Duration::from_millis(10));
-    enigo.key_up(Key::Meta);
+fn paste_output(state: &mut AppState, backend: &mut dyn paste::PasteBackend) {
+    if paste_text(state, backend, state.panel.output.clone()) {
+        record_history(state, "Paste");
+    }
 }
 
-fn apply_copy_internal(state: &mut AppState, action: &str) {
-    if let Err(e) = state.clipboard.set_text(state.panel.output.clone()) {
+/// Send `text` to the active app. Returns whether it got there or at least onto
+/// the clipboard.
+fn paste_text(state: &mut AppState, backend: &mut dyn paste::PasteBackend, text: String) -> bool {
+    // Backends that can insert directly leave the clipboard untouched.
+    match backend.insert_text(&text, state.panel.active_pid) {
+        Ok(true) => return true,
+        Ok(false) => {}
+        Err(err) => eprintln!("Direct insert failed, pasting instead: {}", err),
+    }
+    if let Err(e) = set_clipboard_text(state, text) {
+        state.panel.error = Some(format!("Failed to copy: {}", e));
+        return false;
+    }
+    let ime_wait = std::time::Duration::from_millis(state.cfg.paste.ime_wait_ms);
+    if backend.sends_keystrokes() && !ime::wait_for_composition(ime_wait) {
+        eprintln!("Input method is composing; left the text on the clipboard");
+        state.panel.error =
+            Some("Input method is composing; text copied, paste it when done".to_string());
+        return true;
+    }
+    if let Err(err) = backend.paste() {
+        eprintln!("Paste failed: {}", err);
+        state.panel.error = Some(err.to_string());
+    }
+    true
+}
+
+/// The active app's `max_paste_chars`, when the output is longer than it.
+fn paste_limit(state: &AppState) -> Option<usize> {
+    state
+        .cfg
+        .max_paste_chars(state.panel.active_app.as_deref())
+        .filter(|&max| state.panel.output.chars().count() > max)
+}
+
+/// Paste the next chunk of an over-long output. The panel reopens on the
+/// following chunk until the last one is pasted, then records the paste.
+fn paste_next_chunk(state: &mut AppState) -> bool {
+    let Some(max_chars) = paste_limit(state) else {
+        return apply_paste(state);
+    };
+    let mut backend = match paste::backend(state.cfg.paste.backend) {
+        Ok(backend) => backend,
+        Err(err) => {
+            state.panel.error = Some(err.to_string());
+            return false;
+        }
+    };
+    if !prepare_output(state) || !check_clipboard_unreplaced(state) || !check_policy(state, "Paste")
+    {
+        return false;
+    }
+    let chunks = chunks::split(&state.panel.output, max_chars);
+    let Some(chunk) = chunks.get(state.next_chunk).cloned() else {
+        state.next_chunk = 0;
+        return false;
+    };
+    if !paste_text(state, backend.as_mut(), chunk) {
+        return false;
+    }
+    state.next_chunk += 1;
+    if state.next_chunk == chunks.len() {
+        state.next_chunk = 0;
+        record_history(state, "Paste");
+    }
+    true
+}
+
+fn apply_copy_internal(state: &mut AppState, action: &str) -> bool {
+    if !prepare_output(state) || !check_clipboard_unreplaced(state) || !check_policy(state, action)
+    {
+        return false;
+    }
+    let entry = begin_journal(state, action);
+    write_clipboard(state, action);
+    finish_journal(entry);
+    true
+}
+
+/// Refuse to write output derived from clipboard text that another app has
+/// since replaced; pasting it would silently discard the newer copy.
+fn check_clipboard_unreplaced(state: &mut AppState) -> bool {
+    let Some(expected) = state.panel.clipboard_hash.clone() else {
+        return true;
+    };
+    let current = clipboard(state).and_then(|clipboard| clipboard.get_text().ok());
+    if current.is_some_and(|text| history::content_hash(&text) == expected) {
+        return true;
+    }
+    eprintln!("Clipboard changed since it was read; not writing stale output");
+    state.panel.error = Some(
+        "The clipboard changed since the panel opened, so nothing was pasted. \
+         Press the hotkey again to use the new contents."
+            .to_string(),
+    );
+    false
+}
+
+/// Stop here if a policy blocks the output, noting the attempt in history
+/// without keeping the output.
+fn check_policy(state: &mut AppState, action: &str) -> bool {
+    let Some(decision) = state.policy.as_ref().filter(|decision| decision.blocks()) else {
+        return true;
+    };
+    let summary = decision.summary();
+    let message = format!(
+        "{} blocked by policy: {}",
+        action,
+        decision.reasons.join("; ")
+    );
+    eprintln!("{}", message);
+    state.panel.error = Some(message);
+    let rule_name = selected_rule(state)
+        .map(|rule| rule.name.clone())
+        .unwrap_or_else(|| "No rule".to_string());
+    history::record(
+        &mut state.history,
+        crate::clock::global(),
+        &format!("{} blocked", action),
+        &rule_name,
+        &state.panel.output,
+        true,
+        Some(summary),
+    );
+    false
+}
+
+/// Journal the operation so a crash before `finish_journal` can be reported
+/// (and the clipboard restored) on the next launch. Sensitive rules are not
+/// journaled, since the entry holds the input and hashes of both sides.
+fn begin_journal(state: &mut AppState, action: &str) -> Option<JournalEntry> {
+    let rule = selected_rule(state);
+    if rule.is_some_and(Rule::is_sensitive) {
+        return None;
+    }
+    let rule = rule
+        .map(|rule| rule.name.clone())
+        .unwrap_or_else(|| "No rule".to_string());
+    // Only text read from the clipboard is safe to restore. Merged history, a
+    // restored session, or an image leave the input differing from it.
+    let previous = if state.panel.clipboard_hash.is_some() {
+        clipboard(state).and_then(|clipboard| clipboard.get_text().ok())
+    } else {
+        None
+    };
+    let entry = JournalEntry::begin(
+        action,
+        &rule,
+        &state.panel.input,
+        &state.panel.output,
+        previous,
+    );
+    match journal::write(&entry) {
+        Ok(()) => Some(entry),
+        Err(err) => {
+            eprintln!("Failed to write journal: {}", err);
+            None
+        }
+    }
+}
+
+fn finish_journal(entry: Option<JournalEntry>) {
+    if let Some(entry) = entry
+        && let Err(err) = journal::complete(entry)
+    {
+        eprintln!("Failed to update journal: {}", err);
+    }
+}
+
+/// Finish outputs that need side effects (saving a clipboard image) before use.
+fn prepare_output(state: &mut AppState) -> bool {
+    // Images are only embedded by a rule whose sole transform is `image_embed`.
+    let kind = selected_rule(state).and_then(|rule| match rule.transforms() {
+        [kind] => Some(kind.clone()),
+        _ => None,
+    });
+    if let (
+        Some(TransformKind::ImageEmbed {
+            format,
+            dir,
+            upload_command,
+        }),
+        Some(image),
+    ) = (kind, &state.panel.image)
+    {
+        match image.save(&dir, format, upload_command.as_deref()) {
+            Ok(output) => state.panel.output = output,
+            Err(err) => {
+                state.panel.error = Some(err);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn write_clipboard(state: &mut AppState, action: &str) {
+    if let Err(e) = set_clipboard_text(state, state.panel.output.clone()) {
         state.panel.error = Some(format!("Failed to copy: {}", e));
         return;
     }
@@ -817,64 +2038,89 @@ fn apply_copy_internal(state: &mut AppState, action: &str) {
 }
 
 fn record_history(state: &mut AppState, action: &str) {
+    let sensitive = selected_rule(state).is_some_and(Rule::is_sensitive);
+    if let Some(rule_id) = selected_rule(state).map(|rule| rule.id.clone())
+        && !sensitive
+    {
+        state.metrics.record_applied(&rule_id);
+    }
     let rule_name = selected_rule(state)
         .map(|rule| rule.name.clone())
         .unwrap_or_else(|| "No rule".to_string());
-    let snippet = snippet_text(&state.panel.output);
-    state.history.insert(
-        0,
-        HistoryItem {
-            time: Utc::now(),
-            action: action.to_string(),
-            rule: rule_name,
-            snippet,
-        },
+    history::record(
+        &mut state.history,
+        crate::clock::global(),
+        action,
+        &rule_name,
+        &state.panel.output,
+        sensitive,
+        state.policy.as_ref().map(Decision::summary),
     );
-    if state.history.len() > 5 {
-        state.history.truncate(5);
+    if sensitive {
+        state.masked_hash = Some(history::content_hash(&state.panel.output));
     }
+    state.done_hash = Some(panel_hash(&state.panel));
 }
 
-fn snippet_text(text: &str) -> String {
-    let mut cleaned = text.replace(['\n', '\r'], " ");
-    while cleaned.contains("  ") {
-        cleaned = cleaned.replace("  ", " ");
-    }
-    // Use char count to safely handle UTF-8 multi-byte characters
-    if cleaned.chars().count() > 80 {
-        let truncated: String = cleaned.chars().take(77).collect();
-        format!("{}...", truncated)
-    } else {
-        cleaned
-    }
+/// Identifies the panel's input and output together.
+fn panel_hash(panel: &PanelState) -> String {
+    history::content_hash(&format!("{}\0{}", panel.input, panel.output))
 }
 
-fn content_type_label(content_type: &crate::detect::ContentType) -> String {
+fn content_type_label(content_type: &ContentType) -> String {
     match content_type {
-        crate::detect::ContentType::Json => "json".to_string(),
-        crate::detect::ContentType::Yaml => "yaml".to_string(),
-        crate::detect::ContentType::Text => "text".to_string(),
-        crate::detect::ContentType::List => "list".to_string(),
-        crate::detect::ContentType::Timestamp => "timestamp".to_string(),
+        ContentType::Json => "json".to_string(),
+        ContentType::Yaml => "yaml".to_string(),
+        ContentType::Text => "text".to_string(),
+        ContentType::List => "list".to_string(),
+        ContentType::Timestamp => "timestamp".to_string(),
+        ContentType::Foreign => "foreign".to_string(),
+        ContentType::Long => "long".to_string(),
+        ContentType::Image => "image".to_string(),
+        ContentType::Uuid => "uuid".to_string(),
+        ContentType::Ansi => "ansi".to_string(),
+        ContentType::Ip => "ip".to_string(),
+        ContentType::Email => "email".to_string(),
+        ContentType::Table => "table".to_string(),
+        ContentType::Html => "html".to_string(),
+        ContentType::Xml => "xml".to_string(),
+        ContentType::Base64 => "base64".to_string(),
+        ContentType::Jwt => "jwt".to_string(),
+        ContentType::Color => "color".to_string(),
+        ContentType::Sql => "sql".to_string(),
+        ContentType::Toml => "toml".to_string(),
+        ContentType::Diff => "diff".to_string(),
+        ContentType::Sensitive => "sensitive".to_string(),
+        ContentType::Log => "log".to_string(),
+        ContentType::Shell => "shell".to_string(),
+        ContentType::Binary => "binary".to_string(),
+        ContentType::ContainsIp => "contains_ip".to_string(),
+        ContentType::Custom(name) => name.clone(),
     }
 }
 
-fn ui_rule_with_score(rule: &Rule, score: i32) -> UiRule {
+fn ui_rule_with_score(rule: &Rule, score: i32, ctx: &MatchContext) -> UiRule {
     UiRule {
         id: rule.id.clone(),
         name: rule.name.clone(),
         auto_accept: rule.auto_accept,
-        uses_remote: rule.llm.is_some(),
+        uses_remote: rule.uses_remote(),
         pinned: rule.pinned,
+        sensitive: rule.is_sensitive(),
         score,
         detail: rule_detail(rule),
         match_hint: rule_match_hint(rule),
+        prompt: rule
+            .llm
+            .as_ref()
+            .map(|llm| llm.render_prompt(ctx.active_app.as_deref())),
     }
 }
 
 fn rule_detail(rule: &Rule) -> String {
-    let mut base = if let Some(kind) = rule.transform_kind() {
-        format!("Transform: {}", transform_label(kind))
+    let mut base = if !rule.transforms().is_empty() {
+        let labels: Vec<&str> = rule.transforms().iter().map(transform_label).collect();
+        format!("Transform: {}", labels.join(" → "))
     } else if let Some(llm) = &rule.llm {
         format!("LLM: {}/{}", llm.provider, llm.model)
     } else {
@@ -899,15 +2145,25 @@ fn rule_match_hint(rule: &Rule) -> String {
             .join(", ");
         parts.push(format!("types: {}", list));
     }
+    if let Some(types) = &rule.matchers.exclude_content_types {
+        let list = types
+            .iter()
+            .map(content_type_label)
+            .collect::<Vec<_>>()
+            .join(", ");
+        parts.push(format!("not: {}", list));
+    }
     if let Some(apps) = &rule.matchers.apps {
         let list = apps.join(", ");
         parts.push(format!("apps: {}", list));
     }
+    if let Some(sites) = &rule.matchers.url {
+        parts.push(format!("url: {}", sites.join(", ")));
+    }
     if let Some(regex) = &rule.matchers.regex {
-        let trimmed = if regex.len() > 60 {
-            format!("{}...", &regex[..60])
-        } else {
-            regex.clone()
+        let trimmed = match regex.char_indices().nth(60) {
+            Some((end, _)) => format!("{}...", &regex[..end]),
+            None => regex.clone(),
         };
         parts.push(format!("regex: {}", trimmed));
     }
@@ -918,15 +2174,79 @@ fn rule_match_hint(rule: &Rule) -> String {
     }
 }
 
-fn transform_label(kind: TransformKind) -> &'static str {
+fn transform_label(kind: &TransformKind) -> &'static str {
     match kind {
         TransformKind::JsonPrettify => "json_prettify",
         TransformKind::JsonMinify => "json_minify",
+        TransformKind::Json5ToJson => "json5_to_json",
+        TransformKind::JsonSortKeys => "json_sort_keys",
         TransformKind::JsonToYaml => "json_to_yaml",
         TransformKind::YamlToJson => "yaml_to_json",
+        TransformKind::YamlPrettify { .. } => "yaml_prettify",
         TransformKind::StripFormatting => "strip_formatting",
+        TransformKind::CollapseWhitespace => "collapse_whitespace",
+        TransformKind::StripAnsi => "strip_ansi",
+        TransformKind::HtmlToText => "html_to_text",
+        TransformKind::RedactSecrets => "redact_secrets",
         TransformKind::BulletNormalize => "bullet_normalize",
-        TransformKind::TimestampNormalize => "timestamp_normalize",
+        TransformKind::ListConvert { .. } => "list_convert",
+        TransformKind::TimestampNormalize { .. } => "timestamp_normalize",
+        TransformKind::DedupeLines { .. } => "dedupe_lines",
+        TransformKind::WhitespaceCleanup { .. } => "whitespace_cleanup",
+        TransformKind::Translate { .. } => "translate",
+        TransformKind::RegexReplace { .. } => "regex_replace",
+        TransformKind::ExtractTemplate { .. } => "extract_template",
+        TransformKind::Summarize { .. } => "summarize",
+        TransformKind::ImageEmbed { .. } => "image_embed",
+        TransformKind::Sha256 => "sha256",
+        TransformKind::Sha1 => "sha1",
+        TransformKind::Md5 => "md5",
+        TransformKind::TextStats => "text_stats",
+        TransformKind::UuidGenerate => "uuid_generate",
+        TransformKind::UuidFormat { .. } => "uuid_format",
+        TransformKind::Ipv6Format { .. } => "ipv6_format",
+        TransformKind::CidrRange => "cidr_range",
+        TransformKind::SortIps => "sort_ips",
+        TransformKind::RedactIps => "redact_ips",
+        TransformKind::Truncate { .. } => "truncate",
+        TransformKind::WrapText { .. } => "wrap_text",
+        TransformKind::AddLineNumbers { .. } => "add_line_numbers",
+        TransformKind::StripLineNumbers => "strip_line_numbers",
+        TransformKind::Indent { .. } => "indent",
+        TransformKind::Dedent { .. } => "dedent",
+        TransformKind::QuoteLines => "quote_lines",
+        TransformKind::UnquoteLines { .. } => "unquote_lines",
+        TransformKind::ResolveConflicts { .. } => "resolve_conflicts",
+        TransformKind::TitleCase => "title_case",
+        TransformKind::SentenceCase => "sentence_case",
+        TransformKind::UpperCase => "upper_case",
+        TransformKind::LowerCase => "lower_case",
+        TransformKind::Typographize { .. } => "typographize",
+        TransformKind::AsciiPunctuation => "ascii_punctuation",
+        TransformKind::Autocorrect { .. } => "autocorrect",
+        TransformKind::ShortcodesToEmoji => "shortcodes_to_emoji",
+        TransformKind::EmojiToShortcodes => "emoji_to_shortcodes",
+        TransformKind::MarkdownLinks { .. } => "markdown_links",
+        TransformKind::Rot13 => "rot13",
+        TransformKind::Caesar { .. } => "caesar",
+        TransformKind::PathConvert { .. } => "path_convert",
+        TransformKind::QueryToJson => "query_to_json",
+        TransformKind::CurlToJson => "curl_to_json",
+        TransformKind::JsonToCurl => "json_to_curl",
+        TransformKind::JsonFlatten { .. } => "json_flatten",
+        TransformKind::JsonUnflatten => "json_unflatten",
+        TransformKind::CsvColumns { .. } => "csv_columns",
+        TransformKind::TableTranspose => "table_transpose",
+        TransformKind::DecompressBase64 => "decompress_base64",
+        TransformKind::CompressBase64 { .. } => "compress_base64",
+        TransformKind::HumanizeNumbers { .. } => "humanize_numbers",
+        TransformKind::ParseHumanNumbers => "parse_human_numbers",
+        TransformKind::ParseLocaleNumbers { .. } => "parse_locale_numbers",
+        TransformKind::FormatLocaleNumbers { .. } => "format_locale_numbers",
+        TransformKind::NumberBase { .. } => "number_base",
+        TransformKind::Command { .. } => "command",
+        TransformKind::Script { .. } => "script",
+        TransformKind::Plugin { .. } => "plugin",
     }
 }
 
@@ -935,6 +2255,8 @@ fn current_match_context(state: &AppState) -> MatchContext {
         text: state.panel.input.clone(),
         content_types: state.panel.content_types.clone(),
         active_app: state.panel.active_app.clone(),
+        active_app_id: state.panel.active_app_id.clone(),
+        active_url: state.panel.active_url.clone(),
     }
 }
 
@@ -952,6 +2274,9 @@ fn ui_history_item(item: &HistoryItem) -> UiHistoryItem {
         action: item.action.clone(),
         rule: item.rule.clone(),
         snippet: item.snippet.clone(),
+        count: item.count,
+        mergeable: item.text.is_some(),
+        policy: item.policy.clone(),
     }
 }
 
@@ -977,9 +2302,56 @@ fn compute_stats(panel: &PanelState) -> UiStats {
         after_lines,
         diff_added,
         diff_removed,
+        estimated_tokens: estimate_tokens(&panel.input),
+        before_whitespace: whitespace_stats(&panel.input),
+        after_whitespace: whitespace_stats(&panel.output),
     }
 }
 
+fn whitespace_stats(text: &str) -> UiWhitespaceStats {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    let cr = text.matches('\r').count() - crlf;
+    let line_endings = match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => "none",
+        (true, false, false) => "lf",
+        (false, true, false) => "crlf",
+        (false, false, true) => "cr",
+        _ => "mixed",
+    };
+
+    let (mut tabs, mut spaces, mut trailing_whitespace, mut widest_line) = (0, 0, 0, 0);
+    for line in text.split(['\n', '\r']) {
+        match line.chars().next() {
+            Some('\t') => tabs += 1,
+            Some(' ') if !line.trim().is_empty() => spaces += 1,
+            _ => {}
+        }
+        if line.ends_with([' ', '\t']) {
+            trailing_whitespace += 1;
+        }
+        widest_line = widest_line.max(line.chars().count());
+    }
+    let indentation = match (tabs > 0, spaces > 0) {
+        (false, false) => "none",
+        (true, false) => "tabs",
+        (false, true) => "spaces",
+        (true, true) => "mixed",
+    };
+
+    UiWhitespaceStats {
+        line_endings,
+        indentation,
+        trailing_whitespace,
+        widest_line,
+    }
+}
+
+/// Approximate token count (~4 characters per token for English-like text).
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
 fn diff_line_stats(diff_text: &str) -> (usize, usize) {
     let mut added = 0;
     let mut removed = 0;
@@ -996,6 +2368,64 @@ fn diff_line_stats(diff_text: &str) -> (usize, usize) {
     (added, removed)
 }
 
+/// Save the panel for the next launch; sensitive outputs are never written,
+/// and neither is work already pasted or copied.
+fn save_session(state: &AppState) {
+    if !state.cfg.ui.restore_session
+        || state.panel.input.is_empty()
+        || selected_rule(state).is_some_and(Rule::is_sensitive)
+    {
+        return;
+    }
+    if state.done_hash.as_deref() == Some(panel_hash(&state.panel).as_str()) {
+        // Drop an older session too, so the next launch doesn't offer it.
+        let _ = session::clear();
+        return;
+    }
+    let saved = Session {
+        saved_at: Utc::now(),
+        input: state.panel.input.clone(),
+        output: state.panel.output.clone(),
+        selected_rule_id: state.selected_rule_id.clone(),
+        active_app: state.panel.active_app.clone(),
+    };
+    if let Err(e) = session::save(&saved) {
+        eprintln!("Failed to save session: {}", e);
+    }
+}
+
+fn restore_session(state: &mut AppState, saved: Session) {
+    state.panel.active_app = saved.active_app;
+    state.panel.active_pid = None;
+    state.panel.active_app_id = None;
+    state.panel.active_url = None;
+    load_panel_text(state, saved.input, saved.selected_rule_id);
+    // Show the output as it was, even if the rule has changed since.
+    if state.panel.error.is_none() {
+        state.panel.diff = diff::unified_diff(&state.panel.input, &saved.output);
+        state.panel.output = saved.output;
+    }
+}
+
+/// Replace the panel input with `text`, re-detecting and re-ranking rules.
+/// `rule` is selected when given; otherwise the current selection is kept.
+fn load_panel_text(state: &mut AppState, text: String, rule: Option<String>) {
+    state.panel.image = None;
+    state.panel.binary = None;
+    state.panel.clipboard_hash = None;
+    state.panel.content_types = content_types_for(&state.cfg, &text, None);
+    state.panel.input = text;
+    if rule.is_some() {
+        state.selected_rule_id = rule;
+    }
+    rebuild_suggestions(state);
+    refresh_preview(state);
+}
+
+fn default_merge_separator() -> String {
+    "\n\n".to_string()
+}
+
 fn update_ui_prefs(state: &mut AppState, search: Option<String>, selected: Option<String>) {
     let key = state.panel.active_app_key.clone();
     let entry = state.cfg.ui_state.entry(key).or_default();
@@ -1012,13 +2442,16 @@ fn update_ui_prefs(state: &mut AppState, search: Option<String>, selected: Optio
     if let Some(id) = selected {
         entry.selected_rule_id = Some(id);
     }
+    entry.last_used = Some(Utc::now());
     persist_config(state);
 }
 
 fn persist_config(state: &mut AppState) {
+    state.cfg.prune_ui_state(Utc::now());
     match config::save(&state.cfg) {
         Ok(_) => {
-            state.config_error = None;
+            // A config loaded with problems keeps showing them until fixed.
+            state.config_error = state.cfg.validate().err().map(|err| err.to_string());
             state.config_text = config::load_raw().ok();
             state.config_diff = None;
             state.config_draft_error = None;
@@ -1029,27 +2462,198 @@ fn persist_config(state: &mut AppState) {
     }
 }
 
-fn active_app_name() -> Option<String> {
+fn active_app_name_and_id() -> (Option<String>, Option<String>) {
     match active_win_pos_rs::get_active_window() {
-        Ok(window) => Some(window.app_name),
-        Err(_) => None,
+        Ok(window) => (Some(window.app_name), app_identifier(&window.process_path)),
+        Err(_) => (None, None),
+    }
+}
+
+/// Poll the frontmost app, and its tab URL when it is a browser, so suggestions
+/// track the paste destination, until the returned flag is set. Switching tabs
+/// counts as a change just like switching apps.
+fn spawn_app_tracking(proxy: EventLoopProxy<UserEvent>) -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+    std::thread::spawn(move || {
+        let mut last: Option<(String, Option<String>)> = None;
+        while !stopped.load(Ordering::Relaxed) {
+            std::thread::sleep(ACTIVE_APP_POLL);
+            let Some((name, pid, id)) = external_active_app() else {
+                continue;
+            };
+            let url = id.as_deref().and_then(browser::active_tab_url);
+            let current = (name.clone(), url.clone());
+            if last.as_ref() == Some(&current) || stopped.load(Ordering::Relaxed) {
+                continue;
+            }
+            last = Some(current);
+            if proxy
+                .send_event(UserEvent::ActiveApp { name, pid, id, url })
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    stop
+}
+
+/// Frontmost app name, process, and identifier, ignoring Pasteflow's own panel.
+fn external_active_app() -> Option<(String, u64, Option<String>)> {
+    let window = active_win_pos_rs::get_active_window().ok()?;
+    if window.process_id == u64::from(std::process::id()) {
+        return None;
     }
+    let id = app_identifier(&window.process_path);
+    Some((window.app_name, window.process_id, id))
 }
 
-fn should_handle_hotkey(state: &AppState, id: u32) -> bool {
+/// Identifiers already looked up, by process path; reading a bundle's
+/// identifier runs `defaults`, which is too slow to repeat on every poll.
+static APP_IDENTIFIERS: Lazy<Mutex<HashMap<PathBuf, Option<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The bundle identifier (`com.microsoft.VSCode`) of the app at `path` on
+/// macOS, where `path` is the `.app` bundle; the executable name elsewhere.
+fn app_identifier(path: &Path) -> Option<String> {
+    if path.as_os_str().is_empty() {
+        return None;
+    }
+    let mut cache = APP_IDENTIFIERS.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(path.to_path_buf())
+        .or_insert_with(|| read_app_identifier(path))
+        .clone()
+}
+
+#[cfg(target_os = "macos")]
+fn read_app_identifier(path: &Path) -> Option<String> {
+    let output = std::process::Command::new("defaults")
+        .arg("read")
+        .arg(path.join("Contents/Info"))
+        .arg("CFBundleIdentifier")
+        .output()
+        .ok()?;
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !id.is_empty()).then_some(id)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_app_identifier(path: &Path) -> Option<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+}
+
+/// Whether the clipboard still holds what the panel is showing.
+fn clipboard_unchanged(state: &mut AppState) -> bool {
+    let Some(clipboard) = clipboard(state) else {
+        return false;
+    };
+    let current = clipboard.get_text().map_err(|_| read_non_text(clipboard));
+    match current {
+        Ok(text) => non_text_type(&state.panel).is_none() && text == state.panel.input,
+        Err((image, binary)) => match (&state.panel.image, image) {
+            (Some(shown), Some(current)) => *shown == current,
+            (None, None) => binary.is_some() && binary == state.panel.binary,
+            _ => false,
+        },
+    }
+}
+
+/// The image on a clipboard without text, or else a description of whatever
+/// other data it holds.
+fn read_non_text(clipboard: &mut Clipboard) -> (Option<ClipboardImage>, Option<String>) {
+    match ClipboardImage::read(clipboard) {
+        Some(image) => (Some(image), None),
+        None => (None, images::describe_binary(clipboard)),
+    }
+}
+
+/// The clipboard, reopening it if it failed earlier.
+fn clipboard(state: &mut AppState) -> Option<&mut Clipboard> {
+    if state.clipboard.is_none() {
+        match Clipboard::new() {
+            Ok(clipboard) => {
+                state.clipboard = Some(clipboard);
+                resolve_issue(state, CLIPBOARD_COMPONENT);
+            }
+            Err(e) => {
+                resolve_issue(state, CLIPBOARD_COMPONENT);
+                state.health.push(clipboard_issue(e.to_string()));
+            }
+        }
+    }
+    state.clipboard.as_mut()
+}
+
+/// File extension for exported diffs, so review tools pick a highlighter.
+fn clip_extension(content_types: &[(ContentType, f32)]) -> &'static str {
+    let detected = |content_type| content_types.iter().any(|(t, _)| *t == content_type);
+    if detected(ContentType::Json) {
+        "json"
+    } else if detected(ContentType::Yaml) {
+        "yaml"
+    } else {
+        "txt"
+    }
+}
+
+fn set_clipboard_text(state: &mut AppState, text: String) -> Result<(), String> {
+    // Our own writes are not races; keep tracking what the clipboard holds.
+    let hash = state
+        .panel
+        .clipboard_hash
+        .as_ref()
+        .map(|_| history::content_hash(&text));
+    match clipboard(state) {
+        Some(clipboard) => clipboard.set_text(text).map_err(|e| e.to_string())?,
+        None => return Err("clipboard is unavailable".to_string()),
+    }
+    state.panel.clipboard_hash = hash;
+    Ok(())
+}
+
+const CLIPBOARD_COMPONENT: &str = "Clipboard";
+const HOTKEY_COMPONENT: &str = "Global hotkeys";
+
+fn clipboard_issue(problem: String) -> UiHealthIssue {
+    UiHealthIssue {
+        component: CLIPBOARD_COMPONENT,
+        problem,
+        fix: "Pasteflow retries each time the panel opens. If it keeps failing, quit apps that hold the clipboard and restart Pasteflow.",
+    }
+}
+
+fn hotkey_issue(problem: String) -> UiHealthIssue {
+    UiHealthIssue {
+        component: HOTKEY_COMPONENT,
+        problem,
+        fix: if cfg!(target_os = "macos") {
+            "Allow Pasteflow under System Settings › Privacy & Security › Accessibility, or pick a combo no other app uses, then save the hotkey again."
+        } else {
+            "Pick a combo no other app uses and save the hotkey again; the menu bar icon still opens the panel."
+        },
+    }
+}
+
+fn resolve_issue(state: &mut AppState, component: &str) {
+    state.health.retain(|issue| issue.component != component);
+}
+
+fn should_handle_hotkey(
+    state: &AppState,
+    id: u32,
+    active_app: Option<&str>,
+    active_app_id: Option<&str>,
+) -> bool {
     let Some(rule) = state.hotkey_map.get(&id) else {
         return true;
     };
     if rule.is_global || rule.apps.is_empty() {
         return true;
     }
-    let active = active_app_name().unwrap_or_default().to_lowercase();
-    if active.is_empty() {
-        return false;
-    }
-    rule.apps
-        .iter()
-        .any(|app| active.contains(&app.to_lowercase()))
+    rules::match_apps(&rule.apps, active_app, active_app_id).is_some()
 }
 
 fn apply_hotkeys(state: &mut AppState) -> AppResult<()> {
@@ -1061,15 +2665,27 @@ fn apply_hotkeys(state: &mut AppState) -> AppResult<()> {
         return Err("no valid hotkeys registered".into());
     }
 
+    if state.hotkey_manager.is_none() {
+        match GlobalHotKeyManager::new() {
+            Ok(manager) => state.hotkey_manager = Some(manager),
+            Err(e) => {
+                resolve_issue(state, HOTKEY_COMPONENT);
+                state.health.push(hotkey_issue(e.to_string()));
+                return Err(e.into());
+            }
+        }
+    }
+    let Some(manager) = &state.hotkey_manager else {
+        return Err("hotkey manager unavailable".into());
+    };
+
     if !state.registered_hotkeys.is_empty() {
-        let _ = state
-            .hotkey_manager
-            .unregister_all(&state.registered_hotkeys);
+        let _ = manager.unregister_all(&state.registered_hotkeys);
     }
 
     let mut registered = Vec::new();
     for entry in entries {
-        if state.hotkey_manager.register(entry.hotkey).is_ok() {
+        if manager.register(entry.hotkey).is_ok() {
             registered.push(entry.hotkey);
         } else {
             warnings.push(format!(
@@ -1079,8 +2695,12 @@ fn apply_hotkeys(state: &mut AppState) -> AppResult<()> {
         }
     }
 
+    resolve_issue(state, HOTKEY_COMPONENT);
     if registered.is_empty() {
         state.config_error = Some("Failed to register hotkeys.".to_string());
+        state
+            .health
+            .push(hotkey_issue("no hotkey could be registered".to_string()));
         return Err("failed to register hotkeys".into());
     }
 
@@ -1115,6 +2735,8 @@ fn build_hotkeys(
     for spec in specs {
         match parse_hotkey(&spec.combo) {
             Ok(hotkey) => {
+                // Spellings of the same combo collapse into one entry.
+                let combo = canonical_combo(&spec.combo).unwrap_or(spec.combo);
                 let entry = hotkey_map.entry(hotkey.id()).or_default();
                 if let Some(app) = spec.app {
                     entry.apps.push(app);
@@ -1123,10 +2745,14 @@ fn build_hotkeys(
                 }
                 hotkeys
                     .entry(hotkey.id())
-                    .and_modify(|existing| existing.combos.push(spec.combo.clone()))
+                    .and_modify(|existing| {
+                        if !existing.combos.contains(&combo) {
+                            existing.combos.push(combo.clone());
+                        }
+                    })
                     .or_insert(HotkeyEntry {
                         hotkey,
-                        combos: vec![spec.combo.clone()],
+                        combos: vec![combo],
                     });
             }
             Err(err) => {
@@ -1164,6 +2790,49 @@ fn build_hotkeys(
 }
 
 fn parse_hotkey(combo: &str) -> Result<HotKey, String> {
+    let (modifiers, code) = parse_combo(combo)?;
+    Ok(HotKey::new(Some(modifiers), code))
+}
+
+/// Modifier order used by both the canonical text and glyph forms.
+const COMBO_MODIFIERS: [(Modifiers, &str, &str); 4] = [
+    (Modifiers::META, "Cmd", "⌘"),
+    (Modifiers::CONTROL, "Ctrl", "⌃"),
+    (Modifiers::ALT, "Alt", "⌥"),
+    (Modifiers::SHIFT, "Shift", "⇧"),
+];
+
+/// Canonical spelling of a combo (`cmd+shift+v` → `Cmd+Shift+V`), used when saving.
+fn canonical_combo(combo: &str) -> Result<String, String> {
+    let (modifiers, code) = parse_combo(combo)?;
+    let mut parts: Vec<String> = COMBO_MODIFIERS
+        .iter()
+        .filter(|(flag, _, _)| modifiers.contains(*flag))
+        .map(|(_, name, _)| name.to_string())
+        .collect();
+    parts.push(key_name(code));
+    Ok(parts.join("+"))
+}
+
+/// Display form of a combo: glyphs (`⌘⇧V`) on macOS, canonical text elsewhere.
+/// Unparseable combos are shown as typed.
+fn combo_label(combo: &str) -> String {
+    if !cfg!(target_os = "macos") {
+        return canonical_combo(combo).unwrap_or_else(|_| combo.to_string());
+    }
+    let Ok((modifiers, code)) = parse_combo(combo) else {
+        return combo.to_string();
+    };
+    let mut label: String = COMBO_MODIFIERS
+        .iter()
+        .filter(|(flag, _, _)| modifiers.contains(*flag))
+        .map(|(_, _, glyph)| *glyph)
+        .collect();
+    label.push_str(&key_glyph(code));
+    label
+}
+
+fn parse_combo(combo: &str) -> Result<(Modifiers, Code), String> {
     let parts: Vec<&str> = combo
         .split('+')
         .map(|p| p.trim())
@@ -1192,7 +2861,59 @@ fn parse_hotkey(combo: &str) -> Result<HotKey, String> {
     }
 
     let code = code.ok_or_else(|| "missing key".to_string())?;
-    Ok(HotKey::new(Some(modifiers), code))
+    Ok((modifiers, code))
+}
+
+fn key_name(code: Code) -> String {
+    let name = match code {
+        Code::Enter => "Enter",
+        Code::Escape => "Esc",
+        Code::ArrowUp => "Up",
+        Code::ArrowDown => "Down",
+        Code::ArrowLeft => "Left",
+        Code::ArrowRight => "Right",
+        Code::Backquote => "`",
+        Code::Minus => "-",
+        Code::Equal => "=",
+        Code::BracketLeft => "[",
+        Code::BracketRight => "]",
+        Code::Backslash => "\\",
+        Code::Semicolon => ";",
+        Code::Quote => "'",
+        Code::Comma => ",",
+        Code::Period => ".",
+        Code::Slash => "/",
+        // KeyA → A, Digit1 → 1, F5, Space, PageUp, ...
+        other => {
+            let debug = format!("{:?}", other);
+            return debug
+                .trim_start_matches("Key")
+                .trim_start_matches("Digit")
+                .to_string();
+        }
+    };
+    name.to_string()
+}
+
+fn key_glyph(code: Code) -> String {
+    let glyph = match code {
+        Code::Enter => "↩",
+        Code::Escape => "⎋",
+        Code::Tab => "⇥",
+        Code::Backspace => "⌫",
+        Code::Delete => "⌦",
+        Code::ArrowUp => "↑",
+        Code::ArrowDown => "↓",
+        Code::ArrowLeft => "←",
+        Code::ArrowRight => "→",
+        Code::Home => "↖",
+        Code::End => "↘",
+        Code::PageUp => "⇞",
+        Code::PageDown => "⇟",
+        Code::Space => "Space",
+        other => return key_name(other),
+    };
+    glyph.to_string()
 }
 
 fn code_from_key(key: &str) -> Option<Code> {
@@ -0,0 +1,147 @@
+//! Offline typo fixes for prose: a built-in list of common misspellings, the
+//! rule's own `corrections`, and runs of spaces between words.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+static WORD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[\p{L}']+\b").unwrap());
+static SPACES_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\S)  +(\S)").unwrap());
+
+/// Misspelling and correction, lowercase.
+const TYPOS: &[(&str, &str)] = &[
+    ("accomodate", "accommodate"),
+    ("acheive", "achieve"),
+    ("acknowlege", "acknowledge"),
+    ("adn", "and"),
+    ("adress", "address"),
+    ("agressive", "aggressive"),
+    ("alot", "a lot"),
+    ("apparantly", "apparently"),
+    ("appearence", "appearance"),
+    ("arguement", "argument"),
+    ("assasination", "assassination"),
+    ("basicly", "basically"),
+    ("becasue", "because"),
+    ("becuase", "because"),
+    ("begining", "beginning"),
+    ("beleive", "believe"),
+    ("calender", "calendar"),
+    ("catagory", "category"),
+    ("comming", "coming"),
+    ("commited", "committed"),
+    ("committment", "commitment"),
+    ("completly", "completely"),
+    ("concious", "conscious"),
+    ("definately", "definitely"),
+    ("dependancy", "dependency"),
+    ("dissapoint", "disappoint"),
+    ("embarass", "embarrass"),
+    ("enviroment", "environment"),
+    ("existance", "existence"),
+    ("experiance", "experience"),
+    ("familar", "familiar"),
+    ("finaly", "finally"),
+    ("foriegn", "foreign"),
+    ("freind", "friend"),
+    ("goverment", "government"),
+    ("grammer", "grammar"),
+    ("happend", "happened"),
+    ("hte", "the"),
+    ("immediatly", "immediately"),
+    ("independant", "independent"),
+    ("knowlege", "knowledge"),
+    ("lenght", "length"),
+    ("liason", "liaison"),
+    ("maintainance", "maintenance"),
+    ("millenium", "millennium"),
+    ("mispell", "misspell"),
+    ("neccessary", "necessary"),
+    ("necesary", "necessary"),
+    ("noticable", "noticeable"),
+    ("occassion", "occasion"),
+    ("occured", "occurred"),
+    ("occurence", "occurrence"),
+    ("occurrance", "occurrence"),
+    ("paramter", "parameter"),
+    ("persistant", "persistent"),
+    ("posession", "possession"),
+    ("potentialy", "potentially"),
+    ("prefered", "preferred"),
+    ("privelege", "privilege"),
+    ("probaly", "probably"),
+    ("publically", "publicly"),
+    ("realy", "really"),
+    ("recieve", "receive"),
+    ("recomend", "recommend"),
+    ("refered", "referred"),
+    ("relevent", "relevant"),
+    ("remeber", "remember"),
+    ("repositry", "repository"),
+    ("reponse", "response"),
+    ("resouce", "resource"),
+    ("responsability", "responsibility"),
+    ("seperate", "separate"),
+    ("sucess", "success"),
+    ("succesful", "successful"),
+    ("successfull", "successful"),
+    ("supercede", "supersede"),
+    ("suprise", "surprise"),
+    ("taht", "that"),
+    ("teh", "the"),
+    ("thier", "their"),
+    ("threshhold", "threshold"),
+    ("tomorow", "tomorrow"),
+    ("tommorow", "tomorrow"),
+    ("tommorrow", "tomorrow"),
+    ("truely", "truly"),
+    ("unfortunatly", "unfortunately"),
+    ("untill", "until"),
+    ("usefull", "useful"),
+    ("wierd", "weird"),
+    ("wich", "which"),
+    ("writting", "writing"),
+];
+
+static BUILT_IN: Lazy<HashMap<&'static str, &'static str>> =
+    Lazy::new(|| TYPOS.iter().copied().collect());
+
+/// Fix the typos in `text`, trying `custom` (keys matched ignoring case) before
+/// the built-in list, and collapse runs of spaces between words.
+pub fn correct(text: &str, custom: &BTreeMap<String, String>) -> String {
+    let custom: HashMap<String, &str> = custom
+        .iter()
+        .map(|(from, to)| (from.to_lowercase(), to.as_str()))
+        .collect();
+    let fixed = WORD_RE.replace_all(text, |caps: &regex::Captures| {
+        let word = &caps[0];
+        let lower = word.to_lowercase();
+        let replacement = custom
+            .get(&lower)
+            .copied()
+            .or_else(|| BUILT_IN.get(lower.as_str()).copied());
+        match replacement {
+            Some(replacement) => match_case(word, replacement),
+            None => word.to_string(),
+        }
+    });
+    SPACES_RE.replace_all(&fixed, "$1 $2").into_owned()
+}
+
+/// `replacement` in the case of `word`: `Teh` → `The`, `TEH` → `THE`.
+fn match_case(word: &str, replacement: &str) -> String {
+    let mut chars = word.chars();
+    let first_upper = chars.next().is_some_and(char::is_uppercase);
+    let all_upper = first_upper && word.chars().count() > 1 && chars.all(char::is_uppercase);
+    if all_upper {
+        replacement.to_uppercase()
+    } else if first_upper {
+        let mut out = String::with_capacity(replacement.len());
+        let mut rest = replacement.chars();
+        out.extend(rest.next().map(|c| c.to_uppercase()).into_iter().flatten());
+        out.push_str(rest.as_str());
+        out
+    } else {
+        replacement.to_string()
+    }
+}
@@ -0,0 +1,52 @@
+//! The URL of the frontmost browser tab, for `match.url`. Only macOS exposes
+//! it, through each browser's AppleScript dictionary; Firefox has none.
+
+/// The active tab's URL when the app with bundle identifier `app_id` is a
+/// supported browser.
+pub fn active_tab_url(app_id: &str) -> Option<String> {
+    platform::active_tab_url(app_id)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::Command;
+
+    /// Chromium-based browsers share Chrome's scripting dictionary.
+    const CHROMIUM: &[&str] = &[
+        "com.google.Chrome",
+        "com.google.Chrome.beta",
+        "com.google.Chrome.canary",
+        "com.brave.Browser",
+        "com.microsoft.edgemac",
+        "com.vivaldi.Vivaldi",
+        "com.operasoftware.Opera",
+        "company.thebrowser.Browser",
+        "org.chromium.Chromium",
+    ];
+    const SAFARI: &[&str] = &["com.apple.Safari", "com.apple.SafariTechnologyPreview"];
+
+    pub fn active_tab_url(app_id: &str) -> Option<String> {
+        let query = if CHROMIUM.contains(&app_id) {
+            "URL of active tab of front window"
+        } else if SAFARI.contains(&app_id) {
+            "URL of front document"
+        } else {
+            return None;
+        };
+        let script = format!("tell application id \"{}\" to get {}", app_id, query);
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .output()
+            .ok()?;
+        let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !url.is_empty() && url != "missing value").then_some(url)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub fn active_tab_url(_app_id: &str) -> Option<String> {
+        None
+    }
+}
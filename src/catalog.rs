@@ -0,0 +1,60 @@
+use crate::detect::ContentType;
+use crate::rules::{LlmRule, Rule};
+
+/// Provider and model written into installed presets; edit them in config afterwards.
+const PRESET_PROVIDER: &str = "openai";
+const PRESET_MODEL: &str = "gpt-4o-mini";
+
+/// A ready-made rule template that can be installed into the user's config.
+pub struct Preset {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Prompt template; `{app}` is replaced with the destination app name.
+    pub prompt: &'static str,
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        id: "fix_grammar",
+        name: "Fix Grammar",
+        description: "Correct grammar, spelling, and punctuation without changing tone.",
+        prompt: "Fix grammar, spelling, and punctuation in the following text without changing its meaning or tone. It will be pasted into {app}; keep formatting that suits it. Reply with the corrected text only.",
+    },
+    Preset {
+        id: "make_concise",
+        name: "Make Concise",
+        description: "Tighten wording and remove filler.",
+        prompt: "Rewrite the following text to be as concise as possible while keeping every fact. It will be pasted into {app}. Reply with the rewritten text only.",
+    },
+    Preset {
+        id: "formalize",
+        name: "Formalize",
+        description: "Rewrite in a professional, formal register.",
+        prompt: "Rewrite the following text in a clear, formal, professional tone appropriate for {app}. Keep the meaning unchanged. Reply with the rewritten text only.",
+    },
+    Preset {
+        id: "casualize",
+        name: "Casualize",
+        description: "Rewrite in a friendly, conversational register.",
+        prompt: "Rewrite the following text in a friendly, casual tone appropriate for {app}. Keep the meaning unchanged. Reply with the rewritten text only.",
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.id == id)
+}
+
+impl Preset {
+    pub fn to_rule(&self) -> Rule {
+        let mut rule = Rule::new(self.id, self.name);
+        rule.description = Some(self.description.to_string());
+        rule.llm = Some(LlmRule {
+            provider: PRESET_PROVIDER.to_string(),
+            model: PRESET_MODEL.to_string(),
+            prompt: self.prompt.to_string(),
+        });
+        rule.matchers.content_types = Some(vec![ContentType::Text]);
+        rule
+    }
+}
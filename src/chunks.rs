@@ -0,0 +1,100 @@
+//! Splitting output that is too long for an app's paste limit into numbered
+//! chunks that are pasted one at a time.
+
+/// Split `text` into chunks of at most `max_chars` characters, each prefixed
+/// `(i/n) `, breaking at a blank line, then a line break, then a space when one
+/// falls in the second half of the chunk. Text that fits is returned as is.
+pub fn split(text: &str, max_chars: usize) -> Vec<String> {
+    if text.chars().count() <= max_chars {
+        return vec![text.to_string()];
+    }
+    // The prefix grows with the chunk count; retry until its width is stable.
+    let mut digits = 1;
+    loop {
+        let prefix_len = 2 * digits + 4;
+        if max_chars <= prefix_len {
+            return split_at_boundaries(text, max_chars.max(1))
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+        }
+        let pieces = split_at_boundaries(text, max_chars - prefix_len);
+        let total = pieces.len();
+        if total.to_string().len() <= digits {
+            return pieces
+                .into_iter()
+                .enumerate()
+                .map(|(i, piece)| format!("({}/{}) {}", i + 1, total, piece))
+                .collect();
+        }
+        digits += 1;
+    }
+}
+
+fn split_at_boundaries(text: &str, budget: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    // Keep the first line's indentation; only blank lines around the text go.
+    let mut rest = text.trim_end().trim_start_matches(['\r', '\n']);
+    while !rest.is_empty() {
+        let Some((end, _)) = rest.char_indices().nth(budget) else {
+            pieces.push(rest);
+            break;
+        };
+        let window = &rest[..end];
+        let cut = ["\n\n", "\n", " "]
+            .iter()
+            .find_map(|sep| {
+                window
+                    .rfind(sep)
+                    .filter(|&at| at >= window.len() / 2)
+                    .map(|at| at + sep.len())
+            })
+            .unwrap_or(end);
+        let piece = rest[..cut].trim_end();
+        if !piece.is_empty() {
+            pieces.push(piece);
+        }
+        // The separator is already behind `cut`; what follows keeps its indentation.
+        rest = &rest[cut..];
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split;
+
+    #[test]
+    fn split_numbers_chunks_at_boundaries() {
+        assert_eq!(split("short", 10), vec!["short"]);
+        let text = "First paragraph here.\n\nSecond one is a bit longer than that.";
+        let chunks = split(text, 30);
+        assert_eq!(
+            chunks,
+            vec![
+                "(1/3) First paragraph here.",
+                "(2/3) Second one is a bit",
+                "(3/3) longer than that.",
+            ]
+        );
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 30));
+        let long = "x".repeat(100);
+        let chunks = split(&long, 14);
+        assert_eq!(chunks.len(), 17);
+        assert_eq!(chunks[16], "(17/17) xxxx");
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 14));
+    }
+
+    #[test]
+    fn split_keeps_indentation_of_code() {
+        let code = "fn f() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n}";
+        assert_eq!(
+            split(code, 30),
+            vec![
+                "(1/3) fn f() {\n    let a = 1;",
+                "(2/3)     let b = 2;",
+                "(3/3)     let c = 3;\n}",
+            ]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Source of the current time for relative timestamps and history, so it can be
+//! pinned with `--frozen-time` or in tests.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use std::sync::Mutex;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The wall clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that stays at one instant until it is moved with `advance`.
+pub struct FrozenClock(Mutex<DateTime<Utc>>);
+
+impl FrozenClock {
+    pub fn new(at: DateTime<Utc>) -> Self {
+        Self(Mutex::new(at))
+    }
+
+    #[cfg(test)]
+    pub fn advance(&self, by: chrono::Duration) {
+        let mut at = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        *at += by;
+    }
+}
+
+impl Clock for FrozenClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+static FROZEN: OnceCell<FrozenClock> = OnceCell::new();
+
+/// Pin the process clock to `at`; only the first call takes effect.
+pub fn freeze(at: DateTime<Utc>) {
+    let _ = FROZEN.set(FrozenClock::new(at));
+}
+
+/// The process clock: frozen if `freeze` was called, the wall clock otherwise.
+pub fn global() -> &'static dyn Clock {
+    match FROZEN.get() {
+        Some(clock) => clock,
+        None => &SystemClock,
+    }
+}
+
+/// Parse the `--frozen-time` value: RFC 3339 or epoch seconds.
+pub fn parse_frozen_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    value
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+        .ok_or_else(|| {
+            format!(
+                "--frozen-time needs an RFC 3339 time or epoch seconds, got '{}'",
+                value
+            )
+        })
+}
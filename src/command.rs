@@ -0,0 +1,93 @@
+//! Run an external filter program: the text goes to its stdin and its stdout
+//! comes back, as in `jq .`, `prettier --stdin-filepath x.ts`, or `pandoc`.
+
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running program is checked for exit or timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Pipe `input` through `program args...`, killing it after `timeout`. A single
+/// trailing newline the program adds is dropped unless `input` ended with one.
+pub fn run(
+    program: &str,
+    args: &[String],
+    input: &str,
+    timeout: Duration,
+) -> Result<String, String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start '{}': {}", program, e))?;
+
+    // Feed stdin and drain both pipes on their own threads so a program that
+    // writes before it has read everything cannot deadlock against us.
+    let stdin = child.stdin.take().map(|mut stdin| {
+        let input = input.as_bytes().to_vec();
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        })
+    });
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "'{}' did not finish within {} ms",
+                    program,
+                    timeout.as_millis()
+                ));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("failed to wait for '{}': {}", program, e)),
+        }
+    };
+    if let Some(handle) = stdin {
+        let _ = handle.join();
+    }
+    let stdout = stdout
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    let stderr = stderr
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        let code = status
+            .code()
+            .map_or_else(|| "a signal".to_string(), |code| format!("status {}", code));
+        return Err(match stderr.trim() {
+            "" => format!("'{}' exited with {}", program, code),
+            message => format!("'{}' exited with {}: {}", program, code, message),
+        });
+    }
+    let mut output =
+        String::from_utf8(stdout).map_err(|_| format!("'{}' printed non-UTF-8 output", program))?;
+    if !input.ends_with('\n') && output.ends_with('\n') {
+        output.pop();
+        if output.ends_with('\r') {
+            output.pop();
+        }
+    }
+    Ok(output)
+}
+
+fn drain<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
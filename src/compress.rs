@@ -0,0 +1,120 @@
+//! Base64-wrapped gzip, zlib, and raw deflate payloads, as found in tracing
+//! headers and SAML redirects.
+
+use crate::transforms::Compression;
+use base64::Engine;
+use base64::alphabet;
+use base64::engine::DecodePaddingMode;
+use base64::engine::general_purpose::{GeneralPurpose, PAD};
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+use percent_encoding::percent_decode_str;
+use std::io::{Read, Write};
+
+/// Decoded text stops here, so a small bomb cannot exhaust memory.
+const MAX_INFLATED_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Standard alphabet, with or without `=` padding; URL-safe input is mapped onto it.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    PAD.with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Decode base64 (standard or URL-safe, optionally percent-encoded) and inflate
+/// the result, telling gzip, zlib, and raw deflate apart by their headers.
+pub fn decompress(input: &str) -> Result<String, String> {
+    let trimmed = input.trim();
+    let unescaped = if trimmed.contains('%') {
+        percent_decode_str(trimmed)
+            .decode_utf8()
+            .map_err(|_| "percent-encoding does not decode to base64".to_string())?
+            .into_owned()
+    } else {
+        trimmed.to_string()
+    };
+    let normalized: String = unescaped
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            other => other,
+        })
+        .collect();
+    if normalized.is_empty() {
+        return Err("no base64 payload found".to_string());
+    }
+    let bytes = BASE64
+        .decode(normalized.as_bytes())
+        .map_err(|e| format!("invalid base64: {}", e))?;
+
+    let format = sniff(&bytes);
+    let reader: Box<dyn Read + '_> = match format {
+        Compression::Gzip => Box::new(MultiGzDecoder::new(bytes.as_slice())),
+        Compression::Zlib => Box::new(ZlibDecoder::new(bytes.as_slice())),
+        Compression::Deflate => Box::new(DeflateDecoder::new(bytes.as_slice())),
+    };
+    let mut inflated = Vec::new();
+    reader
+        .take(MAX_INFLATED_BYTES + 1)
+        .read_to_end(&mut inflated)
+        .map_err(|e| format!("invalid {} data: {}", format.name(), e))?;
+    if inflated.len() as u64 > MAX_INFLATED_BYTES {
+        return Err(format!(
+            "payload inflates past {} MiB",
+            MAX_INFLATED_BYTES / 1024 / 1024
+        ));
+    }
+    String::from_utf8(inflated).map_err(|_| "payload is not UTF-8 text".to_string())
+}
+
+/// Compress `input` with `format` and base64-encode it with padding.
+pub fn compress(input: &str, format: Compression) -> Result<String, String> {
+    let level = flate2::Compression::default();
+    let written = match format {
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder
+                .write_all(input.as_bytes())
+                .and_then(|_| encoder.finish())
+        }
+        Compression::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), level);
+            encoder
+                .write_all(input.as_bytes())
+                .and_then(|_| encoder.finish())
+        }
+        Compression::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), level);
+            encoder
+                .write_all(input.as_bytes())
+                .and_then(|_| encoder.finish())
+        }
+    };
+    let bytes = written.map_err(|e| format!("{} compression failed: {}", format.name(), e))?;
+    Ok(BASE64.encode(bytes))
+}
+
+/// Gzip starts with `1f 8b`; a zlib header is a deflate CMF byte whose 16-bit
+/// header is a multiple of 31. Anything else is taken as raw deflate.
+fn sniff(bytes: &[u8]) -> Compression {
+    match bytes {
+        [0x1f, 0x8b, ..] => Compression::Gzip,
+        [cmf, flg, ..]
+            if (cmf & 0x0f) == 8 && ((u16::from(*cmf) << 8) | u16::from(*flg)) % 31 == 0 =>
+        {
+            Compression::Zlib
+        }
+        _ => Compression::Deflate,
+    }
+}
+
+impl Compression {
+    fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zlib => "zlib",
+            Compression::Deflate => "deflate",
+        }
+    }
+}
@@ -1,4 +1,8 @@
+use crate::detect::{ContentType, Detector};
+use crate::paste::PasteBackendKind;
+use crate::policy::Policy;
 use crate::rules::Rule;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -14,6 +18,19 @@ pub struct Config {
     pub rules: Vec<Rule>,
     #[serde(default)]
     pub ui_state: HashMap<String, UiAppState>,
+    #[serde(default)]
+    pub api: ApiConfig,
+    #[serde(default)]
+    pub paste: PasteConfig,
+    /// Per-app settings, keyed by app name as shown in the panel.
+    #[serde(default)]
+    pub apps: HashMap<String, AppProfile>,
+    /// Checks on the output before it is pasted or copied.
+    #[serde(default)]
+    pub policies: Vec<Policy>,
+    /// Content types defined by a regex, detected alongside the built-in ones.
+    #[serde(default)]
+    pub detectors: Vec<Detector>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +43,54 @@ pub struct HotkeyConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UiConfig {
     pub suggestions: usize,
+    /// Rule ids offered when no rule matches the clipboard.
+    #[serde(default = "default_fallback_rules")]
+    pub fallback_rules: Vec<String>,
+    /// ISO 639-1 code; clips detected in another language are tagged `foreign`.
+    #[serde(default = "default_primary_language")]
+    pub primary_language: String,
+    /// Clips with more characters than this are tagged `long`.
+    #[serde(default = "default_long_text_chars")]
+    pub long_text_chars: usize,
+    /// Clips larger than this many bytes are not parsed as JSON or YAML when
+    /// detecting their content type.
+    #[serde(default = "default_parse_limit_bytes")]
+    pub parse_limit_bytes: usize,
+    /// Forget saved per-app UI state after this many days without use (0 keeps it forever).
+    #[serde(default = "default_state_retention_days")]
+    pub state_retention_days: u32,
+    /// Re-rank suggestions when the frontmost app changes while the panel is open.
+    #[serde(default = "default_track_active_app")]
+    pub track_active_app: bool,
+    /// Save the panel on quit and offer to restore it on the next launch.
+    #[serde(default)]
+    pub restore_session: bool,
+    /// Let rules with `auto_accept` paste without the panel; off pauses them all.
+    #[serde(default = "default_true")]
+    pub auto_accept: bool,
+}
+
+/// Local read-only HTTP API (off by default).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_api_bind")]
+    pub bind: String,
+    /// Sent by clients as `Authorization: Bearer <token>`; the API does not
+    /// start without one.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasteConfig {
+    #[serde(default)]
+    pub backend: PasteBackendKind,
+    /// How long to wait for an input method composition to finish before
+    /// leaving the text on the clipboard instead of pasting.
+    #[serde(default = "default_ime_wait_ms")]
+    pub ime_wait_ms: u64,
 }
 
 impl Default for HotkeyConfig {
@@ -39,16 +104,91 @@ impl Default for HotkeyConfig {
 
 impl Default for UiConfig {
     fn default() -> Self {
-        Self { suggestions: 3 }
+        Self {
+            suggestions: 3,
+            fallback_rules: default_fallback_rules(),
+            primary_language: default_primary_language(),
+            long_text_chars: default_long_text_chars(),
+            parse_limit_bytes: default_parse_limit_bytes(),
+            state_retention_days: default_state_retention_days(),
+            track_active_app: default_track_active_app(),
+            restore_session: false,
+            auto_accept: true,
+        }
     }
 }
 
+fn default_fallback_rules() -> Vec<String> {
+    vec!["strip_formatting".to_string(), "json_prettify".to_string()]
+}
+
+fn default_primary_language() -> String {
+    "en".to_string()
+}
+
+fn default_long_text_chars() -> usize {
+    2000
+}
+
+fn default_parse_limit_bytes() -> usize {
+    crate::detect::DEFAULT_PARSE_LIMIT_BYTES
+}
+
+fn default_state_retention_days() -> u32 {
+    90
+}
+
+fn default_track_active_app() -> bool {
+    true
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_api_bind(),
+            token: None,
+        }
+    }
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self {
+            backend: PasteBackendKind::default(),
+            ime_wait_ms: default_ime_wait_ms(),
+        }
+    }
+}
+
+fn default_ime_wait_ms() -> u64 {
+    800
+}
+
+fn default_api_bind() -> String {
+    "127.0.0.1:7433".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AppProfile {
+    /// Longest output the app takes in one paste; longer output is offered in
+    /// numbered chunks.
+    #[serde(default)]
+    pub max_paste_chars: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UiAppState {
     #[serde(default)]
     pub search: Option<String>,
     #[serde(default)]
     pub selected_rule_id: Option<String>,
+    #[serde(default)]
+    pub last_used: Option<DateTime<Utc>>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -57,6 +197,153 @@ pub enum ConfigError {
     Io(#[from] std::io::Error),
     #[error("failed to parse config: {0}")]
     Parse(#[from] toml::de::Error),
+    #[error("invalid config: {0}")]
+    Invalid(String),
+}
+
+impl Config {
+    pub fn max_paste_chars(&self, app: Option<&str>) -> Option<usize> {
+        self.apps.get(app?)?.max_paste_chars.filter(|&max| max > 0)
+    }
+
+    /// Reject configs whose rule ids are empty, malformed, or duplicated, whose
+    /// detectors are invalid, or whose rules match on undefined content types.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut seen = std::collections::HashSet::new();
+        for rule in &self.rules {
+            validate_rule_id(&rule.id)?;
+            if !seen.insert(rule.id.as_str()) {
+                return Err(ConfigError::Invalid(format!(
+                    "duplicate rule id '{}'",
+                    rule.id
+                )));
+            }
+        }
+        let mut detectors = std::collections::HashSet::new();
+        for detector in &self.detectors {
+            validate_detector(detector)?;
+            if !detectors.insert(detector.name.as_str()) {
+                return Err(ConfigError::Invalid(format!(
+                    "duplicate detector '{}'",
+                    detector.name
+                )));
+            }
+        }
+        for rule in &self.rules {
+            let matchers = &rule.matchers;
+            let types = matchers.content_types.iter().flatten();
+            for content_type in types.chain(matchers.exclude_content_types.iter().flatten()) {
+                if let ContentType::Custom(name) = content_type
+                    && !detectors.contains(name.as_str())
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "rule '{}' matches unknown content type '{}'; define it under [[detectors]]",
+                        rule.id, name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rename a rule and every reference to it (saved UI selections, fallbacks).
+    pub fn rename_rule(&mut self, old_id: &str, new_id: &str) -> Result<(), ConfigError> {
+        validate_rule_id(new_id)?;
+        if old_id == new_id {
+            return Ok(());
+        }
+        if self.rules.iter().any(|rule| rule.id == new_id) {
+            return Err(ConfigError::Invalid(format!(
+                "rule id '{}' is already in use",
+                new_id
+            )));
+        }
+        let rule = self
+            .rules
+            .iter_mut()
+            .find(|rule| rule.id == old_id)
+            .ok_or_else(|| ConfigError::Invalid(format!("unknown rule id '{}'", old_id)))?;
+        rule.id = new_id.to_string();
+        for prefs in self.ui_state.values_mut() {
+            if prefs.selected_rule_id.as_deref() == Some(old_id) {
+                prefs.selected_rule_id = Some(new_id.to_string());
+            }
+        }
+        for id in &mut self.ui.fallback_rules {
+            if id == old_id {
+                *id = new_id.to_string();
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop saved UI state that points at deleted rules or belongs to apps not used
+    /// within `ui.state_retention_days`. Returns whether anything changed.
+    pub fn prune_ui_state(&mut self, now: DateTime<Utc>) -> bool {
+        let before = self.ui_state.len();
+        let mut changed = false;
+        let rules = &self.rules;
+        let retention = self.ui.state_retention_days;
+        self.ui_state.retain(|_, prefs| {
+            if let Some(id) = &prefs.selected_rule_id
+                && rules.iter().all(|rule| &rule.id != id)
+            {
+                prefs.selected_rule_id = None;
+                changed = true;
+            }
+            if prefs.last_used.is_none() {
+                prefs.last_used = Some(now);
+                changed = true;
+            }
+            let expired = retention > 0
+                && prefs
+                    .last_used
+                    .is_some_and(|used| now - used > Duration::days(i64::from(retention)));
+            let empty = prefs.search.is_none() && prefs.selected_rule_id.is_none();
+            !expired && !empty
+        });
+        changed || self.ui_state.len() != before
+    }
+}
+
+fn validate_rule_id(id: &str) -> Result<(), ConfigError> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid(format!(
+            "rule id '{}' must be non-empty and use only letters, digits, '_', '-', or '.'",
+            id
+        )))
+    }
+}
+
+fn validate_detector(detector: &Detector) -> Result<(), ConfigError> {
+    let name = &detector.name;
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(ConfigError::Invalid(format!(
+            "detector name '{}' must be non-empty and use only lowercase letters, digits, or '_'",
+            name
+        )));
+    }
+    let parsed: Option<ContentType> =
+        serde_json::from_value(serde_json::Value::String(name.clone())).ok();
+    if !matches!(parsed, Some(ContentType::Custom(_))) {
+        return Err(ConfigError::Invalid(format!(
+            "detector '{}' has the name of a built-in content type",
+            name
+        )));
+    }
+    crate::rules::compile_regex(&detector.regex)
+        .map(|_| ())
+        .map_err(|err| ConfigError::Invalid(format!("detector '{}': {}", name, err)))
 }
 
 pub fn config_path() -> PathBuf {
@@ -67,12 +354,14 @@ pub fn config_path() -> PathBuf {
     root
 }
 
-pub fn load_or_init() -> Result<Config, ConfigError> {
+/// Load the config, writing the defaults on first run. A config that parses
+/// but fails `validate`, such as a typo in `content_types`, still loads; the
+/// problem comes back alongside it so the app can start and show it.
+pub fn load_or_init() -> Result<(Config, Option<ConfigError>), ConfigError> {
     let path = config_path();
     if path.exists() {
         let raw = fs::read_to_string(path)?;
-        let cfg: Config = toml::from_str(&raw)?;
-        return Ok(cfg);
+        return parse_checked(&raw);
     }
 
     if let Some(parent) = path.parent() {
@@ -81,8 +370,13 @@ pub fn load_or_init() -> Result<Config, ConfigError> {
 
     let defaults = include_str!("../config/default.toml");
     fs::write(&path, defaults)?;
-    let cfg: Config = toml::from_str(defaults)?;
-    Ok(cfg)
+    parse_checked(defaults)
+}
+
+fn parse_checked(raw: &str) -> Result<(Config, Option<ConfigError>), ConfigError> {
+    let cfg: Config = toml::from_str(raw)?;
+    let invalid = cfg.validate().err();
+    Ok((cfg, invalid))
 }
 
 pub fn save(cfg: &Config) -> Result<(), ConfigError> {
@@ -105,6 +399,7 @@ pub fn load_raw() -> Result<String, ConfigError> {
 
 pub fn parse_raw(raw: &str) -> Result<Config, ConfigError> {
     let cfg: Config = toml::from_str(raw)?;
+    cfg.validate()?;
     Ok(cfg)
 }
 
@@ -116,3 +411,122 @@ pub fn write_raw(raw: &str) -> Result<(), ConfigError> {
     fs::write(path, raw)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_checked, parse_raw};
+    use crate::detect::{ContentType, detect_custom};
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn rename_rule_updates_references() {
+        let raw = r#"
+[ui]
+suggestions = 3
+fallback_rules = ["a"]
+
+[[rules]]
+id = "a"
+name = "A"
+
+[[rules]]
+id = "b"
+name = "B"
+
+[ui_state.Slack]
+selected_rule_id = "a"
+"#;
+        let mut cfg = parse_raw(raw).unwrap();
+        assert!(cfg.rename_rule("a", "b").is_err());
+        assert!(cfg.rename_rule("a", "has space").is_err());
+        cfg.rename_rule("a", "renamed").unwrap();
+        assert_eq!(cfg.rules[0].id, "renamed");
+        assert_eq!(cfg.ui.fallback_rules, vec!["renamed"]);
+        assert_eq!(
+            cfg.ui_state["Slack"].selected_rule_id.as_deref(),
+            Some("renamed")
+        );
+
+        let duplicate = raw.replace("id = \"b\"", "id = \"a\"");
+        assert!(parse_raw(&duplicate).is_err());
+    }
+
+    #[test]
+    fn detectors_define_content_types_for_rules() {
+        let raw = r#"
+[ui]
+suggestions = 3
+
+[[rules]]
+id = "ticket_link"
+name = "Ticket Link"
+[rules.match]
+content_types = ["ticket"]
+
+[[detectors]]
+name = "ticket"
+regex = "^[A-Z]+-[0-9]+$"
+every_line = true
+"#;
+        let cfg = parse_raw(raw).unwrap();
+        assert_eq!(
+            detect_custom("PF-12\nPF-13\n", &cfg.detectors),
+            [(ContentType::Custom("ticket".to_string()), 1.0)]
+        );
+        assert!(detect_custom("PF-12 and PF-13", &cfg.detectors).is_empty());
+
+        assert!(parse_raw(&raw.replace("[\"ticket\"]", "[\"tickets\"]")).is_err());
+        assert!(parse_raw(&raw.replace("name = \"ticket\"", "name = \"json\"")).is_err());
+        assert!(parse_raw(&raw.replace("+$", "+($")).is_err());
+    }
+
+    #[test]
+    fn load_reports_unknown_content_types() {
+        let raw = r#"
+[ui]
+suggestions = 3
+
+[[rules]]
+id = "json_prettify"
+name = "JSON Prettify"
+[rules.match]
+content_types = ["jsno"]
+"#;
+        let (cfg, invalid) = parse_checked(raw).unwrap();
+        assert_eq!(cfg.rules.len(), 1);
+        assert!(invalid.unwrap().to_string().contains("'jsno'"));
+        let (_, invalid) = parse_checked(&raw.replace("jsno", "json")).unwrap();
+        assert!(invalid.is_none());
+    }
+
+    #[test]
+    fn prune_ui_state_drops_stale_entries() {
+        let raw = r#"
+[ui]
+suggestions = 3
+state_retention_days = 30
+
+[[rules]]
+id = "a"
+name = "A"
+
+[ui_state.Slack]
+selected_rule_id = "a"
+
+[ui_state.Mail]
+selected_rule_id = "deleted"
+
+[ui_state.Notes]
+search = "json"
+last_used = "2020-01-01T00:00:00Z"
+"#;
+        let mut cfg = parse_raw(raw).unwrap();
+        let now = Utc::now();
+        assert!(cfg.prune_ui_state(now));
+        assert_eq!(cfg.ui_state.len(), 1);
+        assert_eq!(cfg.ui_state["Slack"].last_used, Some(now));
+        assert!(!cfg.prune_ui_state(now + Duration::days(1)));
+        assert!(cfg.prune_ui_state(now + Duration::days(31)));
+        assert!(cfg.ui_state.is_empty());
+    }
+}
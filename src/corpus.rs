@@ -0,0 +1,162 @@
+//! `pasteflow detect --corpus DIR [--json]`: run content detection over a
+//! directory of sample clips to check detector changes against real input.
+
+use crate::detect::{ContentType, detect_content_types};
+use serde::Serialize;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+#[derive(Debug, Serialize)]
+struct Sample {
+    file: String,
+    types: Vec<ContentType>,
+    micros: u128,
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut corpus = None;
+    let mut json = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--corpus" => corpus = args.next().map(PathBuf::from),
+            "--json" => json = true,
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+    let corpus = corpus.ok_or("usage: pasteflow detect --corpus DIR [--json]")?;
+
+    let mut files = Vec::new();
+    collect_files(&corpus, &mut files).map_err(|e| format!("{}: {}", corpus.display(), e))?;
+    files.sort();
+    let samples: Vec<Sample> = files
+        .iter()
+        .map(|path| {
+            let bytes = fs::read(path).unwrap_or_default();
+            let text = String::from_utf8_lossy(&bytes);
+            let started = Instant::now();
+            let types = detect_content_types(&text)
+                .into_iter()
+                .map(|(content_type, _)| content_type)
+                .collect();
+            Sample {
+                file: path
+                    .strip_prefix(&corpus)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string(),
+                types,
+                micros: started.elapsed().as_micros(),
+            }
+        })
+        .collect();
+
+    if json {
+        let out = serde_json::to_string_pretty(&samples).map_err(|e| e.to_string())?;
+        println!("{}", out);
+    } else {
+        print!("{}", matrix(&samples));
+    }
+    Ok(())
+}
+
+pub fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path
+            .file_name()
+            .is_some_and(|name| !name.to_string_lossy().starts_with('.'))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn type_name(content_type: &ContentType) -> String {
+    serde_json::to_value(content_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// One row per file, one column per type seen anywhere, then per-type totals.
+fn matrix(samples: &[Sample]) -> String {
+    let mut columns: Vec<ContentType> = Vec::new();
+    for sample in samples {
+        for content_type in &sample.types {
+            if !columns.contains(content_type) {
+                columns.push(content_type.clone());
+            }
+        }
+    }
+    let names: Vec<String> = columns.iter().map(type_name).collect();
+    let file_width = samples
+        .iter()
+        .map(|sample| sample.file.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("file".len());
+
+    let mut out = String::new();
+    let _ = write!(out, "{:<file_width$}", "file");
+    for name in &names {
+        let _ = write!(out, "  {}", name);
+    }
+    let _ = writeln!(out, "  {:>8}", "µs");
+    for sample in samples {
+        let _ = write!(out, "{:<file_width$}", sample.file);
+        for (column, name) in columns.iter().zip(&names) {
+            let mark = if sample.types.contains(column) {
+                "x"
+            } else {
+                "."
+            };
+            let _ = write!(out, "  {:^width$}", mark, width = name.len());
+        }
+        let _ = writeln!(out, "  {:>8}", sample.micros);
+    }
+    let _ = write!(out, "{:<file_width$}", "total");
+    for (column, name) in columns.iter().zip(&names) {
+        let count = samples
+            .iter()
+            .filter(|sample| sample.types.contains(column))
+            .count();
+        let _ = write!(out, "  {:^width$}", count, width = name.len());
+    }
+    let total: u128 = samples.iter().map(|sample| sample.micros).sum();
+    let _ = writeln!(out, "  {:>8}", total);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sample, matrix};
+    use crate::detect::ContentType;
+
+    #[test]
+    fn matrix_marks_detected_types() {
+        let samples = vec![
+            Sample {
+                file: "a.json".to_string(),
+                types: vec![ContentType::Text, ContentType::Json],
+                micros: 12,
+            },
+            Sample {
+                file: "notes.txt".to_string(),
+                types: vec![ContentType::Text],
+                micros: 3,
+            },
+        ];
+        let out = matrix(&samples);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "file       text  json        µs");
+        assert_eq!(lines[1], "a.json      x     x          12");
+        assert_eq!(lines[2], "notes.txt   x     .           3");
+        assert_eq!(lines[3], "total       2     1          15");
+    }
+}
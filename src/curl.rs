@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Structured form of an HTTP request, as produced by `curl_to_json`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpRequest {
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// JSON bodies are embedded as JSON; anything else is a string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Options whose value is the next argument (or attached, as in `-XPOST`).
+const VALUE_OPTIONS: [&str; 21] = [
+    "-X",
+    "--request",
+    "-H",
+    "--header",
+    "-d",
+    "--data",
+    "--data-raw",
+    "--data-binary",
+    "--data-ascii",
+    "--data-urlencode",
+    "--json",
+    "-b",
+    "--cookie",
+    "-u",
+    "--user",
+    "-A",
+    "--user-agent",
+    "-e",
+    "--referer",
+    "--url",
+    "-o",
+];
+
+/// Parse a cURL command line, such as one copied from browser devtools.
+pub fn parse(command: &str) -> Result<HttpRequest, String> {
+    let args = split_shell(command)?;
+    let mut args = args.into_iter().peekable();
+    match args.next() {
+        Some(first) if first == "curl" || first.ends_with("/curl") => {}
+        _ => return Err("not a curl command".to_string()),
+    }
+
+    let mut method = None;
+    let mut url = None;
+    let mut headers = BTreeMap::new();
+    let mut data: Vec<String> = Vec::new();
+    let mut get = false;
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "-" {
+            url.get_or_insert(arg);
+            continue;
+        }
+        if arg == "-G" || arg == "--get" {
+            get = true;
+            continue;
+        }
+        let (option, value) = match split_option(&arg) {
+            Some((option, attached)) => {
+                let value = match attached {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", option))?,
+                };
+                (option, value)
+            }
+            // Flags such as --compressed, -s, -L, -k carry no value.
+            None => continue,
+        };
+        match option.as_str() {
+            "-X" | "--request" => method = Some(value.to_uppercase()),
+            "-H" | "--header" => {
+                if let Some((name, header_value)) = value.split_once(':') {
+                    headers.insert(name.trim().to_string(), header_value.trim().to_string());
+                }
+            }
+            "--json" => {
+                headers
+                    .entry("Content-Type".to_string())
+                    .or_insert_with(|| "application/json".to_string());
+                data.push(value);
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii"
+            | "--data-urlencode" => data.push(value),
+            "-b" | "--cookie" => {
+                headers.insert("Cookie".to_string(), value);
+            }
+            "-u" | "--user" => {
+                headers.insert("Authorization".to_string(), basic_auth(&value));
+            }
+            "-A" | "--user-agent" => {
+                headers.insert("User-Agent".to_string(), value);
+            }
+            "-e" | "--referer" => {
+                headers.insert("Referer".to_string(), value);
+            }
+            "--url" => url = Some(value),
+            _ => {}
+        }
+    }
+
+    let mut url = url.ok_or_else(|| "missing URL".to_string())?;
+    let mut body = None;
+    if !data.is_empty() {
+        let joined = data.join("&");
+        if get {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&joined);
+        } else {
+            body = Some(serde_json::from_str(&joined).unwrap_or(serde_json::Value::String(joined)));
+        }
+    }
+    let method = method.unwrap_or_else(|| {
+        if body.is_some() {
+            "POST".to_string()
+        } else {
+            "GET".to_string()
+        }
+    });
+    Ok(HttpRequest {
+        method,
+        url,
+        headers,
+        body,
+    })
+}
+
+/// Render a request as a multi-line cURL command.
+pub fn build(request: &HttpRequest) -> String {
+    let implied = if request.body.is_some() {
+        "POST"
+    } else {
+        "GET"
+    };
+    let mut first = "curl".to_string();
+    if !request.method.eq_ignore_ascii_case(implied) {
+        first.push_str(&format!(" -X {}", request.method.to_uppercase()));
+    }
+    first.push(' ');
+    first.push_str(&shell_quote(&request.url));
+    let mut parts = vec![first];
+    for (name, value) in &request.headers {
+        parts.push(format!(
+            "-H {}",
+            shell_quote(&format!("{}: {}", name, value))
+        ));
+    }
+    match &request.body {
+        Some(serde_json::Value::String(text)) => {
+            parts.push(format!("--data-raw {}", shell_quote(text)))
+        }
+        Some(value) => parts.push(format!("--data-raw {}", shell_quote(&value.to_string()))),
+        None => {}
+    }
+    parts.join(" \\\n  ")
+}
+
+/// Split `--data=x` / `-XPOST` into option and attached value; `None` for flags.
+fn split_option(arg: &str) -> Option<(String, Option<String>)> {
+    if let Some((option, value)) = arg.split_once('=')
+        && option.starts_with("--")
+        && VALUE_OPTIONS.contains(&option)
+    {
+        return Some((option.to_string(), Some(value.to_string())));
+    }
+    if VALUE_OPTIONS.contains(&arg) {
+        return Some((arg.to_string(), None));
+    }
+    if !arg.starts_with("--")
+        && let (Some(option), Some(value)) = (arg.get(..2), arg.get(2..))
+        && !value.is_empty()
+        && VALUE_OPTIONS.contains(&option)
+    {
+        return Some((option.to_string(), Some(value.to_string())));
+    }
+    None
+}
+
+fn basic_auth(credentials: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in credentials.as_bytes().chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    format!("Basic {}", encoded)
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// POSIX-style word splitting with single, double, and `$'...'` quotes and
+/// backslash line continuations.
+fn split_shell(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(escaped) => {
+                    current.push(escaped);
+                    in_word = true;
+                }
+                None => {}
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('t') => current.push('\t'),
+                            Some('r') => current.push('\r'),
+                            Some(c) => current.push(c),
+                            None => return Err("unterminated $' quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated $' quote".to_string()),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
@@ -1,5 +1,23 @@
 use similar::TextDiff;
 
+/// Unified diff for pasting into a review or ticket: `a/` and `b/` headers named
+/// `file_name`, optionally fenced as a Markdown `diff` block.
+pub fn export_diff(before: &str, after: &str, file_name: &str, fenced: bool) -> String {
+    let diff = TextDiff::from_lines(before, after);
+    let mut out = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", file_name), &format!("b/{}", file_name))
+        .to_string();
+    if fenced {
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out = format!("```diff\n{}```\n", out);
+    }
+    out
+}
+
 pub fn unified_diff(before: &str, after: &str) -> String {
     let diff = TextDiff::from_lines(before, after);
     diff.unified_diff()
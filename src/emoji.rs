@@ -0,0 +1,100 @@
+//! Convert between `:shortcode:` syntax (GitHub's names, plus Slack's
+//! `:skin-tone-N:` suffix) and Unicode emoji.
+
+use emojis::{Emoji, SkinTone};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static SHORTCODE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r":([a-z0-9_+\-]+):(?::skin-tone-([2-6]):)?").unwrap());
+
+/// Longest emoji sequence tried, in chars (family ZWJ sequences run to 11).
+const MAX_EMOJI_CHARS: usize = 12;
+
+/// Skin tones in the order of Slack's `:skin-tone-2:` to `:skin-tone-6:`.
+const SKIN_TONES: [SkinTone; 5] = [
+    SkinTone::Light,
+    SkinTone::MediumLight,
+    SkinTone::Medium,
+    SkinTone::MediumDark,
+    SkinTone::Dark,
+];
+
+/// Replace every known `:shortcode:` with its emoji; unknown ones are kept.
+/// Returns `None` when nothing was replaced.
+pub fn shortcodes_to_emoji(input: &str) -> Option<String> {
+    let mut found = false;
+    let out = SHORTCODE_RE.replace_all(input, |caps: &regex::Captures| {
+        let Some(emoji) = emojis::get_by_shortcode(&caps[1]) else {
+            return caps[0].to_string();
+        };
+        found = true;
+        let toned = caps
+            .get(2)
+            .and_then(|tone| tone.as_str().parse::<usize>().ok())
+            .and_then(|n| emoji.with_skin_tone(SKIN_TONES[n - 2]));
+        // A tone on an emoji that takes none is dropped, as Slack does.
+        toned.unwrap_or(emoji).as_str().to_string()
+    });
+    found.then(|| out.into_owned())
+}
+
+/// Replace every emoji that has a shortcode with `:shortcode:`. Symbols such as
+/// `©` or `™` are only converted when written with the emoji selector U+FE0F.
+/// Returns `None` when nothing was replaced.
+pub fn emoji_to_shortcodes(input: &str) -> Option<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut found = false;
+    let mut rest = input;
+    while let Some(first) = rest.chars().next() {
+        match longest_emoji(rest) {
+            Some((len, shortcode)) => {
+                out.push_str(&shortcode);
+                rest = &rest[len..];
+                found = true;
+            }
+            None => {
+                out.push(first);
+                rest = &rest[first.len_utf8()..];
+            }
+        }
+    }
+    found.then_some(out)
+}
+
+/// The byte length and shortcode of the longest emoji at the start of `text`.
+fn longest_emoji(text: &str) -> Option<(usize, String)> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    let second = chars.next();
+    // Plain ASCII is only the start of a keycap such as `1️⃣`.
+    if first.is_ascii() && !matches!(second, Some('\u{fe0f}' | '\u{20e3}')) {
+        return None;
+    }
+    let ends: Vec<usize> = text
+        .char_indices()
+        .skip(1)
+        .map(|(i, _)| i)
+        .chain([text.len()])
+        .take(MAX_EMOJI_CHARS)
+        .collect();
+    ends.iter().rev().find_map(|&end| {
+        let candidate = &text[..end];
+        let emoji = emojis::get(candidate)?;
+        // A lone symbol with text presentation, like `©`, is not an emoji.
+        if end == first.len_utf8() && emoji.as_str() != candidate {
+            return None;
+        }
+        Some((end, shortcode(emoji)?))
+    })
+}
+
+fn shortcode(emoji: &Emoji) -> Option<String> {
+    if let Some(code) = emoji.shortcode() {
+        return Some(format!(":{}:", code));
+    }
+    let tone = emoji.skin_tone()?;
+    let n = SKIN_TONES.iter().position(|t| *t == tone)? + 2;
+    let base = emoji.with_skin_tone(SkinTone::Default)?.shortcode()?;
+    Some(format!(":{}::skin-tone-{}:", base, n))
+}
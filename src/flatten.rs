@@ -0,0 +1,224 @@
+//! Flatten nested JSON into one `path = value` entry per leaf and back, e.g.
+//! `{"a": {"b": [{"c": 1}]}}` ⇄ `a.b[0].c = 1`. Keys that are not plain
+//! identifiers are quoted: `paths["src/main.rs"] = true`.
+
+use serde_json::{Map, Value};
+
+/// Array slots `unflatten` may fill with `null` beyond one per entry, so a
+/// stray `items[99999999]` cannot allocate a huge array.
+const MAX_PADDING: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Every leaf of `value` with its path. Empty objects and arrays are leaves, so
+/// they survive a round trip.
+pub fn flatten(value: &Value) -> Result<Vec<(String, Value)>, String> {
+    if !matches!(value, Value::Object(_) | Value::Array(_)) {
+        return Err("expected a JSON object or array".to_string());
+    }
+    let mut out = Vec::new();
+    walk(value, &mut String::new(), &mut out);
+    Ok(out)
+}
+
+fn walk(value: &Value, path: &mut String, out: &mut Vec<(String, Value)>) {
+    let len = path.len();
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                push_key(path, key);
+                walk(child, path, out);
+                path.truncate(len);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, child) in items.iter().enumerate() {
+                path.push_str(&format!("[{}]", i));
+                walk(child, path, out);
+                path.truncate(len);
+            }
+        }
+        leaf => out.push((path.clone(), leaf.clone())),
+    }
+}
+
+fn push_key(path: &mut String, key: &str) {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-'));
+    if plain {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key);
+    } else {
+        path.push('[');
+        path.push_str(&Value::String(key.to_string()).to_string());
+        path.push(']');
+    }
+}
+
+/// `path = value` lines; values are JSON, so strings keep their quotes.
+pub fn to_lines(entries: &[(String, Value)]) -> String {
+    entries
+        .iter()
+        .map(|(path, value)| format!("{} = {}", path, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read `to_lines` output, or a flat JSON object of `path: value`. A value that
+/// is not valid JSON is taken as a bare string, so `app.name = pasteflow` works.
+pub fn parse_entries(input: &str) -> Result<Vec<(String, Value)>, String> {
+    if let Ok(Value::Object(map)) = serde_json::from_str::<Value>(input) {
+        return Ok(map.into_iter().collect());
+    }
+    let mut entries = Vec::new();
+    for (n, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (segments, rest) = parse_path(line).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        let Some(raw) = rest.trim_start().strip_prefix('=') else {
+            return Err(format!("line {}: expected 'path = value'", n + 1));
+        };
+        let raw = raw.trim();
+        let value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+        entries.push((render_path(&segments), value));
+    }
+    Ok(entries)
+}
+
+/// Rebuild nested JSON from `entries`. Missing array slots become `null`.
+pub fn unflatten(entries: &[(String, Value)]) -> Result<Value, String> {
+    let mut root: Option<Value> = None;
+    let mut padding = entries.len() + MAX_PADDING;
+    for (path, value) in entries {
+        let (segments, rest) = parse_path(path)?;
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected '{}' in path '{}'", rest.trim(), path));
+        }
+        let Some(first) = segments.first() else {
+            return Err("empty path".to_string());
+        };
+        let slot = root.get_or_insert_with(|| empty_for(first));
+        insert(slot, &segments, value.clone(), &mut padding)
+            .map_err(|e| format!("'{}' {}", path, e))?;
+    }
+    root.ok_or_else(|| "no entries to unflatten".to_string())
+}
+
+fn empty_for(segment: &Segment) -> Value {
+    match segment {
+        Segment::Key(_) => Value::Object(Map::new()),
+        Segment::Index(_) => Value::Array(Vec::new()),
+    }
+}
+
+/// Set the value at `segments` under `slot`, spending `padding` on the nulls
+/// needed to reach an array index.
+fn insert(
+    slot: &mut Value,
+    segments: &[Segment],
+    value: Value,
+    padding: &mut usize,
+) -> Result<(), &'static str> {
+    const CONFLICT: &str = "conflicts with another entry";
+    let Some((segment, rest)) = segments.split_first() else {
+        if slot.is_null() {
+            *slot = value;
+            return Ok(());
+        }
+        return Err(CONFLICT);
+    };
+    if slot.is_null() {
+        *slot = empty_for(segment);
+    }
+    let child = match (segment, slot) {
+        (Segment::Key(key), Value::Object(map)) => map.entry(key.clone()).or_insert(Value::Null),
+        (Segment::Index(i), Value::Array(items)) => {
+            if items.len() <= *i {
+                *padding = padding
+                    .checked_sub(i + 1 - items.len())
+                    .ok_or("skips too many array slots")?;
+                items.resize(i + 1, Value::Null);
+            }
+            &mut items[*i]
+        }
+        _ => return Err(CONFLICT),
+    };
+    insert(child, rest, value, padding)
+}
+
+/// Parse a path prefix of `input`, returning its segments and the rest.
+fn parse_path(input: &str) -> Result<(Vec<Segment>, &str), String> {
+    let mut segments = Vec::new();
+    let mut rest = input;
+    loop {
+        if let Some(after) = rest.strip_prefix('[') {
+            let (segment, after) = parse_bracket(after)?;
+            segments.push(segment);
+            rest = after;
+            continue;
+        }
+        let after = match rest.strip_prefix('.') {
+            Some(after) => after,
+            None if segments.is_empty() => rest,
+            None => break,
+        };
+        let end = after
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-')))
+            .unwrap_or(after.len());
+        if end == 0 {
+            if segments.is_empty() {
+                return Err("expected a key".to_string());
+            }
+            break;
+        }
+        segments.push(Segment::Key(after[..end].to_string()));
+        rest = &after[end..];
+    }
+    Ok((segments, rest))
+}
+
+/// After a `[`: a `"quoted key"]` or a `number]`.
+fn parse_bracket(input: &str) -> Result<(Segment, &str), String> {
+    if input.starts_with('"') {
+        let mut stream = serde_json::Deserializer::from_str(input).into_iter::<String>();
+        let key = match stream.next() {
+            Some(Ok(key)) => key,
+            _ => return Err("unterminated quoted key".to_string()),
+        };
+        let after = &input[stream.byte_offset()..];
+        let after = after
+            .strip_prefix(']')
+            .ok_or_else(|| "expected ']' after quoted key".to_string())?;
+        return Ok((Segment::Key(key), after));
+    }
+    let end = input.find(']').ok_or_else(|| "expected ']'".to_string())?;
+    let index = input[..end]
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not an array index", &input[..end]))?;
+    Ok((Segment::Index(index), &input[end + 1..]))
+}
+
+fn render_path(segments: &[Segment]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) => push_key(&mut path, key),
+            Segment::Index(i) => path.push_str(&format!("[{}]", i)),
+        }
+    }
+    path
+}
@@ -0,0 +1,204 @@
+use crate::clock::Clock;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// Entries kept in the Recent panel.
+const HISTORY_LIMIT: usize = 5;
+
+pub struct HistoryItem {
+    /// When this entry was last seen.
+    pub time: DateTime<Utc>,
+    pub action: String,
+    pub rule: String,
+    pub snippet: String,
+    /// Full output for `merge`; not kept for sensitive rules.
+    pub text: Option<String>,
+    /// `content_hash` of the output.
+    pub hash: String,
+    /// Consecutive identical actions collapsed into this entry.
+    pub count: u32,
+    /// Why a policy warned about or blocked this action.
+    pub policy: Option<String>,
+}
+
+/// Snippet shown for outputs that are not kept.
+const HIDDEN_SNIPPET: &str = "(hidden)";
+
+/// Record an action at the front of `history`. Repeating the latest entry
+/// (same action, rule, and output) bumps its count and time instead.
+/// `sensitive` outputs are hashed but get no snippet.
+pub fn record(
+    history: &mut Vec<HistoryItem>,
+    clock: &dyn Clock,
+    action: &str,
+    rule: &str,
+    output: &str,
+    sensitive: bool,
+    policy: Option<String>,
+) {
+    let hash = content_hash(output);
+    if let Some(latest) = history.first_mut()
+        && latest.hash == hash
+        && latest.action == action
+        && latest.rule == rule
+    {
+        latest.count += 1;
+        latest.time = clock.now();
+        latest.policy = policy;
+        return;
+    }
+    history.insert(
+        0,
+        HistoryItem {
+            time: clock.now(),
+            action: action.to_string(),
+            rule: rule.to_string(),
+            snippet: if sensitive {
+                HIDDEN_SNIPPET.to_string()
+            } else {
+                snippet_text(output)
+            },
+            text: (!sensitive).then(|| output.to_string()),
+            hash,
+            count: 1,
+            policy,
+        },
+    );
+    history.truncate(HISTORY_LIMIT);
+}
+
+/// Join the outputs at `indexes` (in the order given) with `separator`.
+pub fn merge(
+    history: &[HistoryItem],
+    indexes: &[usize],
+    separator: &str,
+) -> Result<String, String> {
+    let mut parts = Vec::with_capacity(indexes.len());
+    for &index in indexes {
+        let item = history
+            .get(index)
+            .ok_or_else(|| format!("history entry {} no longer exists", index + 1))?;
+        let text = item
+            .text
+            .as_deref()
+            .ok_or_else(|| format!("'{}' output was not kept", item.rule))?;
+        parts.push(text);
+    }
+    if parts.is_empty() {
+        return Err("select history entries to merge".to_string());
+    }
+    Ok(parts.join(separator))
+}
+
+/// Hex SHA-256 of clipboard text, used to compare content without keeping it.
+pub fn content_hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn snippet_text(text: &str) -> String {
+    let mut cleaned = text.replace(['\n', '\r'], " ");
+    while cleaned.contains("  ") {
+        cleaned = cleaned.replace("  ", " ");
+    }
+    // Use char count to safely handle UTF-8 multi-byte characters
+    if cleaned.chars().count() > 80 {
+        let truncated: String = cleaned.chars().take(77).collect();
+        format!("{}...", truncated)
+    } else {
+        cleaned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, record};
+    use crate::clock::FrozenClock;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn consecutive_duplicates_collapse() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = FrozenClock::new(start);
+        let mut history = Vec::new();
+        record(
+            &mut history,
+            &clock,
+            "Paste",
+            "JSON Prettify",
+            "{}",
+            false,
+            None,
+        );
+        clock.advance(Duration::seconds(5));
+        record(
+            &mut history,
+            &clock,
+            "Paste",
+            "JSON Prettify",
+            "{}",
+            false,
+            None,
+        );
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].count, 2);
+        assert_eq!(history[0].time, start + Duration::seconds(5));
+
+        record(
+            &mut history,
+            &clock,
+            "Copy",
+            "JSON Prettify",
+            "{}",
+            false,
+            None,
+        );
+        record(
+            &mut history,
+            &clock,
+            "Paste",
+            "JSON Prettify",
+            "{}",
+            false,
+            None,
+        );
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].count, 1);
+
+        for n in 0..10 {
+            clock.advance(Duration::seconds(1));
+            record(
+                &mut history,
+                &clock,
+                "Copy",
+                "Plain",
+                &n.to_string(),
+                false,
+                None,
+            );
+        }
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[0].snippet, "9");
+        assert!(history.windows(2).all(|pair| pair[0].time > pair[1].time));
+        assert_eq!(history[0].time, start + Duration::seconds(15));
+
+        let warning = "Warned: Slack is on the deny list".to_string();
+        record(
+            &mut history,
+            &clock,
+            "Paste",
+            "Secret",
+            "hunter2",
+            true,
+            Some(warning.clone()),
+        );
+        assert!(!history[0].snippet.contains("hunter2"));
+        assert_eq!(history[0].policy, Some(warning));
+
+        assert_eq!(merge(&history, &[1, 2], "\n").unwrap(), "9\n8");
+        assert!(merge(&history, &[0], "\n").is_err());
+        assert!(merge(&history, &[9], "\n").is_err());
+    }
+}
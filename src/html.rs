@@ -0,0 +1,372 @@
+//! Reducing HTML to readable plain text: tags dropped, block elements turned
+//! into line breaks, list items into bullets, and entities decoded.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+
+static ENTITY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[A-Za-z][A-Za-z0-9]{1,7});").unwrap()
+});
+
+/// Elements whose content is never shown.
+const HIDDEN: &[&str] = &["script", "style", "head", "template", "noscript"];
+/// Elements that start and end a paragraph.
+const PARAGRAPHS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "pre",
+    "table",
+    "hr",
+];
+/// Elements that start and end a line.
+const LINES: &[&str] = &[
+    "div",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "main",
+    "ul",
+    "ol",
+    "tr",
+    "dl",
+    "dt",
+    "dd",
+    "figure",
+    "figcaption",
+    "address",
+    "form",
+    "fieldset",
+];
+
+/// Element names that mark text as HTML rather than XML or prose with a stray `<`.
+const KNOWN: &[&str] = &[
+    "html",
+    "head",
+    "body",
+    "title",
+    "meta",
+    "link",
+    "div",
+    "span",
+    "p",
+    "a",
+    "img",
+    "br",
+    "hr",
+    "ul",
+    "ol",
+    "li",
+    "table",
+    "thead",
+    "tbody",
+    "tr",
+    "td",
+    "th",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "b",
+    "i",
+    "u",
+    "em",
+    "strong",
+    "code",
+    "pre",
+    "blockquote",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "nav",
+    "main",
+    "form",
+    "input",
+    "button",
+    "label",
+    "select",
+    "option",
+    "script",
+    "style",
+    "iframe",
+    "small",
+    "sup",
+    "sub",
+    "figure",
+    "figcaption",
+];
+/// Share of the text that must be markup when there is no doctype.
+const MIN_MARKUP_RATIO: f64 = 0.1;
+
+/// Whether `input` looks like an HTML page or fragment: a doctype or `<html>`,
+/// or at least two known tags making up a tenth of the text.
+pub fn is_html(input: &str) -> bool {
+    let start = input.trim_start();
+    let head = start.get(..14).unwrap_or(start).to_ascii_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return true;
+    }
+    let mut known = 0usize;
+    let mut markup = 0usize;
+    let mut rest = input;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open..];
+        match Tag::parse(rest) {
+            Some(tag) => {
+                if KNOWN.contains(&tag.name.as_str()) {
+                    known += 1;
+                }
+                markup += tag.len;
+                rest = &rest[tag.len..];
+            }
+            None => rest = &rest[1..],
+        }
+    }
+    known >= 2 && markup as f64 >= input.len() as f64 * MIN_MARKUP_RATIO
+}
+
+/// Plain text from an HTML fragment or document.
+pub fn to_text(input: &str) -> String {
+    let mut out = TextBuilder::default();
+    // `None` for `<ul>`, or the next number of an `<ol>`.
+    let mut lists: Vec<Option<usize>> = Vec::new();
+    let mut pre = 0usize;
+    let mut rest = input;
+    while let Some(open) = rest.find('<') {
+        out.text(&rest[..open], pre > 0);
+        rest = &rest[open..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(tag) = Tag::parse(rest) else {
+            out.text("<", pre > 0);
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[tag.len..];
+        let name = tag.name.as_str();
+        if !tag.closing && HIDDEN.contains(&name) {
+            rest = skip_element(rest, name);
+            continue;
+        }
+        match name {
+            "br" => out.line_break(),
+            "li" if !tag.closing => {
+                out.block(1);
+                let depth = lists.len().saturating_sub(1);
+                let marker = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                out.marker(&format!("{}{}", "  ".repeat(depth), marker));
+            }
+            "ul" | "ol" => {
+                if tag.closing {
+                    lists.pop();
+                } else {
+                    lists.push((name == "ol").then_some(1));
+                }
+                out.block(if lists.is_empty() { 2 } else { 1 });
+            }
+            "td" | "th" if !tag.closing => out.text(" ", false),
+            _ if PARAGRAPHS.contains(&name) => {
+                if name == "pre" {
+                    pre = if tag.closing {
+                        pre.saturating_sub(1)
+                    } else {
+                        pre + 1
+                    };
+                }
+                out.block(2);
+            }
+            _ if LINES.contains(&name) => out.block(1),
+            _ => {}
+        }
+    }
+    out.text(rest, pre > 0);
+    out.finish()
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    /// Bytes up to and including the closing `>`.
+    len: usize,
+}
+
+impl Tag {
+    /// Parse the tag at the start of `input`, or `None` if the `<` is just text.
+    fn parse(input: &str) -> Option<Self> {
+        let body = input.strip_prefix('<')?;
+        let (closing, body) = match body.strip_prefix('/') {
+            Some(body) => (true, body),
+            None => (false, body),
+        };
+        if !body.starts_with(|c: char| c.is_ascii_alphabetic() || c == '!' || c == '?') {
+            return None;
+        }
+        let name: String = body
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        // Skip quoted attribute values, which may contain `>`.
+        let mut quote = None;
+        for (at, ch) in input.char_indices() {
+            match (quote, ch) {
+                (Some(q), ch) if ch == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(ch),
+                (None, '>') => {
+                    return Some(Self {
+                        name,
+                        closing,
+                        len: at + 1,
+                    });
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// The text after `</name>`, or nothing if the element is never closed.
+fn skip_element<'a>(input: &'a str, name: &str) -> &'a str {
+    let lower = input.to_ascii_lowercase();
+    let close = format!("</{}", name);
+    match lower.find(&close) {
+        Some(at) => {
+            let after = &input[at..];
+            after.find('>').map_or("", |end| &after[end + 1..])
+        }
+        None => "",
+    }
+}
+
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    ENTITY_RE.replace_all(text, |caps: &Captures| {
+        let entity = &caps[1];
+        let decoded = if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else if let Some(decimal) = entity.strip_prefix('#') {
+            decimal.parse().ok().and_then(char::from_u32)
+        } else {
+            named_entity(entity)
+        };
+        decoded.map_or_else(|| caps[0].to_string(), String::from)
+    })
+}
+
+fn named_entity(name: &str) -> Option<char> {
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        "euro" => '€',
+        "pound" => '£',
+        _ => return None,
+    })
+}
+
+/// Collapses whitespace the way a browser would and tracks owed line breaks.
+#[derive(Default)]
+struct TextBuilder {
+    out: String,
+    /// Newlines to emit before the next text.
+    breaks: usize,
+    space: bool,
+}
+
+impl TextBuilder {
+    fn text(&mut self, raw: &str, pre: bool) {
+        let text = decode_entities(raw);
+        if pre {
+            if !text.is_empty() {
+                self.flush();
+                self.out.push_str(&text);
+            }
+            return;
+        }
+        for ch in text.chars() {
+            if ch.is_whitespace() {
+                self.space = true;
+            } else {
+                if self.breaks == 0 && self.space && !self.out.ends_with([' ', '\n']) {
+                    self.out.push(' ');
+                }
+                self.flush();
+                self.out.push(ch);
+            }
+        }
+    }
+
+    /// Start the next text at least `newlines` lines down.
+    fn block(&mut self, newlines: usize) {
+        self.breaks = self.breaks.max(newlines);
+    }
+
+    fn line_break(&mut self) {
+        self.breaks = (self.breaks + 1).min(2);
+    }
+
+    fn marker(&mut self, marker: &str) {
+        self.flush();
+        self.out.push_str(marker);
+    }
+
+    fn flush(&mut self) {
+        if !self.out.is_empty() {
+            for _ in 0..self.breaks {
+                self.out.push('\n');
+            }
+        }
+        self.breaks = 0;
+        self.space = false;
+    }
+
+    fn finish(self) -> String {
+        self.out
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+}
@@ -0,0 +1,182 @@
+use crate::transforms::ImageEmbedFormat;
+use arboard::Clipboard;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long `upload_command` may run before it is killed; it runs on the
+/// event loop, so a hung upload would otherwise freeze the app.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Image read from the clipboard, kept PNG-encoded until it is saved.
+#[derive(PartialEq, Eq)]
+pub struct ClipboardImage {
+    pub width: usize,
+    pub height: usize,
+    png: Vec<u8>,
+}
+
+impl ClipboardImage {
+    pub fn read(clipboard: &mut Clipboard) -> Option<Self> {
+        let data = clipboard.get_image().ok()?;
+        let rgba = image::RgbaImage::from_raw(
+            data.width as u32,
+            data.height as u32,
+            data.bytes.into_owned(),
+        )?;
+        let mut png = Vec::new();
+        rgba.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .ok()?;
+        Some(Self {
+            width: data.width,
+            height: data.height,
+            png,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "Image {}×{} (PNG, {} KB)",
+            self.width,
+            self.height,
+            self.png.len().div_ceil(1024)
+        )
+    }
+
+    /// Content-addressed file name so preview and save agree on the path.
+    fn file_name(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.png.hash(&mut hasher);
+        format!("pasteflow-{:016x}.png", hasher.finish())
+    }
+
+    fn target_path(&self, dir: &str) -> PathBuf {
+        expand_home(dir).join(self.file_name())
+    }
+
+    /// Reference to where the image will be saved. Has no side effects.
+    pub fn preview(&self, dir: &str, format: ImageEmbedFormat) -> String {
+        let path = self.target_path(dir);
+        embed_reference(format, &path.display().to_string(), self)
+    }
+
+    /// Write the PNG, run the upload command if configured, and return the reference.
+    ///
+    /// `{path}` in the upload command is replaced with the quoted file path; the first
+    /// non-empty line the command prints becomes the image URL. The command is
+    /// killed after `UPLOAD_TIMEOUT`.
+    pub fn save(
+        &self,
+        dir: &str,
+        format: ImageEmbedFormat,
+        upload_command: Option<&str>,
+    ) -> Result<String, String> {
+        let path = self.target_path(dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, &self.png).map_err(|e| format!("Failed to save image: {}", e))?;
+        let path_text = path.display().to_string();
+
+        let location = match upload_command {
+            Some(command) if !command.trim().is_empty() => {
+                let quoted = format!("'{}'", path_text.replace('\'', r"'\''"));
+                let script = command.replace("{path}", &quoted);
+                let stdout =
+                    crate::command::run("sh", &["-c".to_string(), script], "", UPLOAD_TIMEOUT)
+                        .map_err(|e| format!("Upload command failed: {}", e))?;
+                stdout
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .ok_or_else(|| "Upload command printed no URL".to_string())?
+            }
+            _ => path_text,
+        };
+        Ok(embed_reference(format, &location, self))
+    }
+}
+
+/// Describe clipboard data that has neither a text nor an image form, such as
+/// copied files or rich text without plain text. `None` when there is none.
+pub fn describe_binary(clipboard: &mut Clipboard) -> Option<String> {
+    if let Ok(files) = clipboard.get().file_list()
+        && !files.is_empty()
+    {
+        return Some(describe_files(&files));
+    }
+    let html = clipboard
+        .get()
+        .html()
+        .ok()
+        .filter(|html| !html.is_empty())?;
+    Some(format!(
+        "Rich text without plain text (HTML, {} KB)",
+        html.len().div_ceil(1024)
+    ))
+}
+
+/// `2 files: a.pdf, b.png`, naming at most three.
+fn describe_files(files: &[PathBuf]) -> String {
+    let mut names: Vec<String> = files
+        .iter()
+        .take(3)
+        .map(|path| match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => path.display().to_string(),
+        })
+        .collect();
+    if files.len() > 3 {
+        names.push(format!("and {} more", files.len() - 3));
+    }
+    let noun = if files.len() == 1 { "file" } else { "files" };
+    format!("{} {}: {}", files.len(), noun, names.join(", "))
+}
+
+fn embed_reference(format: ImageEmbedFormat, location: &str, image: &ClipboardImage) -> String {
+    match format {
+        ImageEmbedFormat::Markdown => {
+            if location.contains(' ') {
+                format!("![image](<{}>)", location)
+            } else {
+                format!("![image]({})", location)
+            }
+        }
+        ImageEmbedFormat::Html => format!(
+            "<img src=\"{}\" width=\"{}\" height=\"{}\" alt=\"image\">",
+            location.replace('&', "&amp;").replace('"', "&quot;"),
+            image.width,
+            image.height
+        ),
+    }
+}
+
+fn expand_home(dir: &str) -> PathBuf {
+    if let Some(rest) = dir.strip_prefix("~/")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest);
+    }
+    PathBuf::from(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copied_files_are_described_by_name() {
+        let files: Vec<PathBuf> = ["/tmp/a.pdf", "/tmp/b.png", "/tmp/c", "/tmp/d", "/tmp/e"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(describe_files(&files[..1]), "1 file: a.pdf");
+        assert_eq!(
+            describe_files(&files),
+            "5 files: a.pdf, b.png, c, and 2 more"
+        );
+    }
+}
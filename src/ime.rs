@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+
+const POLL: Duration = Duration::from_millis(50);
+/// Pause before pasting while an input method is selected, so a composition
+/// committed by the panel closing lands first.
+const SETTLE: Duration = Duration::from_millis(120);
+
+/// Input method state of the frontmost app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(any(target_os = "macos", windows)), allow(dead_code))]
+pub enum ImeState {
+    /// A plain keyboard layout; keystrokes go straight to the app.
+    Inactive,
+    /// An input method (CJK, etc.) is selected but nothing is being composed.
+    Active,
+    /// Text is mid-composition; a synthetic paste would corrupt it.
+    Composing,
+}
+
+pub fn current() -> ImeState {
+    platform::current()
+}
+
+/// Wait up to `max` for a composition to finish. Returns false if it is still
+/// in progress and the paste keystroke should not be sent.
+pub fn wait_for_composition(max: Duration) -> bool {
+    let deadline = Instant::now() + max;
+    loop {
+        match current() {
+            ImeState::Inactive => return true,
+            ImeState::Active => {
+                std::thread::sleep(SETTLE);
+                return true;
+            }
+            ImeState::Composing if Instant::now() >= deadline => return false,
+            ImeState::Composing => std::thread::sleep(POLL),
+        }
+    }
+}
+
+/// macOS exposes the selected input source but not composition state, so any
+/// input method mode counts as `Active`.
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::ImeState;
+    use std::ffi::c_void;
+
+    type CFTypeRef = *const c_void;
+
+    #[link(name = "Carbon", kind = "framework")]
+    unsafe extern "C" {
+        static kTISPropertyInputSourceType: CFTypeRef;
+        static kTISTypeKeyboardInputMode: CFTypeRef;
+        fn TISCopyCurrentKeyboardInputSource() -> CFTypeRef;
+        fn TISGetInputSourceProperty(source: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFEqual(a: CFTypeRef, b: CFTypeRef) -> bool;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    pub fn current() -> ImeState {
+        // SAFETY: the copied source is released; the property is borrowed from it.
+        unsafe {
+            let source = TISCopyCurrentKeyboardInputSource();
+            if source.is_null() {
+                return ImeState::Inactive;
+            }
+            let kind = TISGetInputSourceProperty(source, kTISPropertyInputSourceType);
+            let input_mode = !kind.is_null() && CFEqual(kind, kTISTypeKeyboardInputMode);
+            CFRelease(source);
+            if input_mode {
+                ImeState::Active
+            } else {
+                ImeState::Inactive
+            }
+        }
+    }
+}
+
+/// IMM reports the open status for any window, but composition strings only
+/// for windows on our thread; otherwise an open IME counts as `Active`.
+#[cfg(windows)]
+mod platform {
+    use super::ImeState;
+    use std::ffi::c_void;
+    use std::ptr;
+
+    type Hwnd = *mut c_void;
+    type Himc = *mut c_void;
+
+    const WM_IME_CONTROL: u32 = 0x0283;
+    const IMC_GETOPENSTATUS: usize = 0x0005;
+    const GCS_COMPSTR: u32 = 0x0008;
+
+    #[link(name = "user32")]
+    unsafe extern "system" {
+        fn GetForegroundWindow() -> Hwnd;
+        fn SendMessageW(hwnd: Hwnd, msg: u32, wparam: usize, lparam: isize) -> isize;
+    }
+
+    #[link(name = "imm32")]
+    unsafe extern "system" {
+        fn ImmGetDefaultIMEWnd(hwnd: Hwnd) -> Hwnd;
+        fn ImmGetContext(hwnd: Hwnd) -> Himc;
+        fn ImmReleaseContext(hwnd: Hwnd, himc: Himc) -> i32;
+        fn ImmGetCompositionStringW(himc: Himc, index: u32, buf: *mut c_void, len: u32) -> i32;
+    }
+
+    pub fn current() -> ImeState {
+        // SAFETY: handles come from the system and null results are checked.
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_null() {
+                return ImeState::Inactive;
+            }
+            let himc = ImmGetContext(hwnd);
+            if !himc.is_null() {
+                let pending = ImmGetCompositionStringW(himc, GCS_COMPSTR, ptr::null_mut(), 0);
+                ImmReleaseContext(hwnd, himc);
+                if pending > 0 {
+                    return ImeState::Composing;
+                }
+            }
+            let ime_wnd = ImmGetDefaultIMEWnd(hwnd);
+            if !ime_wnd.is_null()
+                && SendMessageW(ime_wnd, WM_IME_CONTROL, IMC_GETOPENSTATUS, 0) != 0
+            {
+                ImeState::Active
+            } else {
+                ImeState::Inactive
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    use super::ImeState;
+
+    pub fn current() -> ImeState {
+        ImeState::Inactive
+    }
+}
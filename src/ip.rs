@@ -0,0 +1,184 @@
+//! IPv4 and IPv6 addresses and CIDR ranges: IPv6 expansion and compression,
+//! CIDR to first and last address, numeric sorting, redaction, and detection.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Runs of characters an address or CIDR range can contain; candidates are
+/// confirmed with `std::net` parsing, so times like `12:30:45` are skipped.
+static CANDIDATE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[0-9A-Fa-f]*[:.][0-9A-Fa-f:.]*[0-9A-Fa-f:](?:/[0-9]{1,3})?").unwrap()
+});
+
+/// Rewrite every IPv6 address in full (`2001:0db8:0000:...`) or in the
+/// shortest RFC 5952 form (`2001:db8::1`). Returns `None` when there is none.
+pub fn format_ipv6(input: &str, expand: bool) -> Option<String> {
+    let mut found = false;
+    let out = CANDIDATE_RE.replace_all(input, |caps: &regex::Captures| {
+        let token = &caps[0];
+        let (address, prefix) = split_prefix(token);
+        let Ok(addr) = address.parse::<Ipv6Addr>() else {
+            return token.to_string();
+        };
+        found = true;
+        let text = if expand {
+            addr.segments()
+                .iter()
+                .map(|segment| format!("{:04x}", segment))
+                .collect::<Vec<_>>()
+                .join(":")
+        } else {
+            addr.to_string()
+        };
+        format!("{}{}", text, prefix)
+    });
+    found.then(|| out.into_owned())
+}
+
+/// Replace every CIDR range with its first and last address, `first - last`.
+/// Returns `None` when there is none.
+pub fn cidr_ranges(input: &str) -> Option<String> {
+    let mut found = false;
+    let out = CANDIDATE_RE.replace_all(input, |caps: &regex::Captures| {
+        let token = &caps[0];
+        match parse_cidr(token) {
+            Some((first, last)) => {
+                found = true;
+                format!("{} - {}", first, last)
+            }
+            None => token.to_string(),
+        }
+    });
+    found.then(|| out.into_owned())
+}
+
+/// The first and last address of `10.0.0.0/8` or `2001:db8::/32`; host bits
+/// set in the address are ignored.
+fn parse_cidr(token: &str) -> Option<(IpAddr, IpAddr)> {
+    let (address, prefix) = token.split_once('/')?;
+    let prefix: u32 = prefix.parse().ok()?;
+    match address.parse::<IpAddr>().ok()? {
+        IpAddr::V4(addr) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            let first = u32::from(addr) & mask;
+            Some((
+                Ipv4Addr::from(first).into(),
+                Ipv4Addr::from(first | !mask).into(),
+            ))
+        }
+        IpAddr::V6(addr) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            let first = u128::from(addr) & mask;
+            Some((
+                Ipv6Addr::from(first).into(),
+                Ipv6Addr::from(first | !mask).into(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Sort lines by the address they start with, numerically and IPv4 first.
+/// Lines without one keep their order after the rest. Returns `None` when no
+/// line starts with an address.
+pub fn sort_lines(input: &str) -> Option<String> {
+    let mut lines: Vec<(Option<IpAddr>, &str)> = input
+        .lines()
+        .map(|line| (leading_address(line), line))
+        .collect();
+    if lines.iter().all(|(addr, _)| addr.is_none()) {
+        return None;
+    }
+    // `None` sorts first, so flip it to put those lines last.
+    lines.sort_by_key(|(addr, _)| (addr.is_none(), *addr));
+    let mut out = lines
+        .iter()
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if input.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn leading_address(line: &str) -> Option<IpAddr> {
+    let token = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .find(|token| !token.is_empty())?;
+    split_prefix(token).0.parse().ok()
+}
+
+/// Whether `input` is only IP addresses and CIDR ranges, separated by
+/// whitespace or commas.
+pub fn is_ip_list(input: &str) -> bool {
+    let mut tokens = input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .peekable();
+    tokens.peek().is_some()
+        && tokens.all(|token| {
+            let (address, prefix) = split_prefix(token);
+            address.parse::<IpAddr>().is_ok() && (prefix.is_empty() || parse_cidr(token).is_some())
+        })
+}
+
+/// Whether an IP address or CIDR range appears anywhere in `input`, such as
+/// in a log line or a config snippet.
+pub fn contains_address(input: &str) -> bool {
+    CANDIDATE_RE
+        .find_iter(input)
+        .any(|found| is_address(input, found))
+}
+
+/// Mask every IP address and CIDR range as `****`, like `redact_secrets`.
+/// Returns `None` when there is none.
+pub fn redact(input: &str) -> Option<String> {
+    let mut found = false;
+    let mut out = String::with_capacity(input.len());
+    let mut last = 0;
+    for candidate in CANDIDATE_RE.find_iter(input) {
+        if is_address(input, candidate) {
+            found = true;
+            out.push_str(&input[last..candidate.start()]);
+            out.push_str("****");
+            last = candidate.end();
+        }
+    }
+    out.push_str(&input[last..]);
+    found.then_some(out)
+}
+
+/// A candidate that parses as an address or CIDR range and stands on its
+/// own, not inside a word or a longer dotted run such as `Vec::new` or the
+/// version `1.2.3.4.5`. A trailing full stop is fine. A bare `::` parses as
+/// the unspecified address but is far more often an operator, so it needs a
+/// hex digit.
+fn is_address(input: &str, candidate: regex::Match) -> bool {
+    let before = input[..candidate.start()].chars().next_back();
+    let mut after = input[candidate.end()..].chars();
+    let joined = |c: char| c.is_alphanumeric() || c == '_';
+    if before.is_some_and(|c| joined(c) || c == '.' || c == ':') {
+        return false;
+    }
+    match after.next() {
+        Some('.') if after.next().is_some_and(|c| joined(c) || c == '.') => return false,
+        Some(c) if joined(c) || c == '/' => return false,
+        _ => {}
+    }
+    let token = candidate.as_str();
+    let (address, prefix) = split_prefix(token);
+    if !address.contains(|c: char| c.is_ascii_hexdigit()) {
+        return false;
+    }
+    address.parse::<IpAddr>().is_ok() && (prefix.is_empty() || parse_cidr(token).is_some())
+}
+
+/// `("10.0.0.0", "/8")` from `10.0.0.0/8`; the prefix is empty when absent.
+fn split_prefix(token: &str) -> (&str, &str) {
+    match token.find('/') {
+        Some(i) => token.split_at(i),
+        None => (token, ""),
+    }
+}
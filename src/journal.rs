@@ -0,0 +1,140 @@
+use crate::clock::Clock;
+use crate::history::content_hash;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// The last clipboard operation, written before the clipboard is touched and
+/// marked complete afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub time: DateTime<Utc>,
+    pub action: String,
+    pub rule: String,
+    pub input_hash: String,
+    pub output_hash: String,
+    pub completed: bool,
+    /// Clipboard text before the operation, when it was read from the
+    /// clipboard; only kept until the operation completes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+}
+
+/// How far an interrupted operation got, judged from the current clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The clipboard still holds the input.
+    NotStarted,
+    /// The output reached the clipboard, but the paste may not have been sent.
+    ClipboardWritten,
+    /// The clipboard has changed since.
+    Unknown,
+}
+
+impl JournalEntry {
+    pub fn begin(
+        action: &str,
+        rule: &str,
+        input: &str,
+        output: &str,
+        previous: Option<String>,
+    ) -> Self {
+        Self {
+            time: crate::clock::global().now(),
+            action: action.to_string(),
+            rule: rule.to_string(),
+            input_hash: content_hash(input),
+            output_hash: content_hash(output),
+            completed: false,
+            previous,
+        }
+    }
+
+    pub fn outcome(&self, clipboard: Option<&str>) -> Outcome {
+        match clipboard.map(content_hash) {
+            Some(hash) if hash == self.output_hash => Outcome::ClipboardWritten,
+            Some(hash) if hash == self.input_hash => Outcome::NotStarted,
+            _ => Outcome::Unknown,
+        }
+    }
+
+    pub fn describe(&self, clipboard: Option<&str>) -> String {
+        let status = match self.outcome(clipboard) {
+            Outcome::NotStarted => "the clipboard was not changed",
+            Outcome::ClipboardWritten => "the result was copied but may not have been pasted",
+            Outcome::Unknown => "the clipboard has changed since",
+        };
+        format!(
+            "Pasteflow quit during {} with {} at {}: {}.",
+            self.action.to_lowercase(),
+            self.rule,
+            self.time.with_timezone(&chrono::Local).format("%H:%M:%S"),
+            status
+        )
+    }
+}
+
+pub fn journal_path() -> PathBuf {
+    crate::config::config_path().with_file_name("journal.json")
+}
+
+/// Durably record `entry` before the clipboard is mutated.
+pub fn write(entry: &JournalEntry) -> io::Result<()> {
+    let path = journal_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Write then rename so a crash never leaves a torn journal.
+    let tmp = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(&serde_json::to_vec(entry)?)?;
+    file.sync_all()?;
+    fs::rename(tmp, path)
+}
+
+/// Mark `entry` complete and drop the saved clipboard text.
+pub fn complete(mut entry: JournalEntry) -> io::Result<()> {
+    entry.completed = true;
+    entry.previous = None;
+    write(&entry)
+}
+
+/// The last operation, if it never completed.
+pub fn load_interrupted() -> Option<JournalEntry> {
+    let raw = fs::read(journal_path()).ok()?;
+    let entry: JournalEntry = serde_json::from_slice(&raw).ok()?;
+    (!entry.completed).then_some(entry)
+}
+
+pub fn clear() -> io::Result<()> {
+    match fs::remove_file(journal_path()) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JournalEntry, Outcome};
+
+    #[test]
+    fn outcome_follows_clipboard_contents() {
+        let entry = JournalEntry::begin(
+            "Paste",
+            "JSON Prettify",
+            "{\"a\":1}",
+            "{\n  \"a\": 1\n}",
+            Some("{\"a\":1}".to_string()),
+        );
+        assert_eq!(entry.outcome(Some("{\"a\":1}")), Outcome::NotStarted);
+        assert_eq!(
+            entry.outcome(Some("{\n  \"a\": 1\n}")),
+            Outcome::ClipboardWritten
+        );
+        assert_eq!(entry.outcome(Some("other")), Outcome::Unknown);
+        assert_eq!(entry.outcome(None), Outcome::Unknown);
+        assert_eq!(entry.previous.as_deref(), Some("{\"a\":1}"));
+    }
+}
@@ -1,12 +1,69 @@
+mod api;
 mod app;
+mod autocorrect;
+mod browser;
+mod catalog;
+mod chunks;
+mod clock;
+mod command;
+mod compress;
 mod config;
+mod corpus;
+mod curl;
 mod detect;
 mod diff;
+mod emoji;
+mod flatten;
+mod history;
+mod html;
+mod images;
+mod ime;
+mod ip;
+mod journal;
+mod mdlinks;
+mod metrics;
+mod numbers;
+mod paste;
+mod paths;
+mod plugins;
+mod policy;
 mod rules;
+mod script;
+mod session;
+mod soak;
+mod table;
 mod transforms;
 
 fn main() {
-    if let Err(err) = app::run() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--frozen-time") {
+        let end = (index + 2).min(args.len());
+        let value = args.drain(index..end).nth(1).unwrap_or_default();
+        match clock::parse_frozen_time(&value) {
+            Ok(at) => clock::freeze(at),
+            Err(err) => {
+                eprintln!("pasteflow: {err}");
+                std::process::exit(2);
+            }
+        }
+    }
+    let command = match args.first().map(String::as_str) {
+        Some("detect") => Some(corpus::run as fn(&[String]) -> Result<(), String>),
+        Some("soak") => Some(soak::run as fn(&[String]) -> Result<(), String>),
+        _ => None,
+    };
+    if let Some(command) = command {
+        if let Err(err) = command(&args[1..]) {
+            eprintln!("pasteflow {}: {err}", args[0]);
+            std::process::exit(2);
+        }
+        return;
+    }
+
+    let options = app::RunOptions {
+        dev: args.iter().any(|arg| arg == "--dev"),
+    };
+    if let Err(err) = app::run(options) {
         eprintln!("Pasteflow failed: {err}");
     }
 }
@@ -0,0 +1,181 @@
+//! Convert Markdown links and images between inline `[text](url "title")` and
+//! reference style `[text][1]`, with the `[1]: url "title"` definitions
+//! collected at the bottom. Code spans and fenced blocks are left alone.
+
+use crate::transforms::{LinkStyle, map_prose};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// `[text](url "title")`, with `!` for images; the URL may hold one level of
+/// balanced parentheses, as Wikipedia links do.
+static INLINE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(!?)\[((?:[^\[\]\n\\]|\\.)*)\]\(\s*(<[^<>\n]*>|[^\s()<>]+(?:\([^\s()]*\)[^\s()<>]*)*)(?:\s+("[^"\n]*"|'[^'\n]*'))?\s*\)"#,
+    )
+    .unwrap()
+});
+/// `[text][label]`, `[text][]`, or a bare `[label]`.
+static REFERENCE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(!?)\[((?:[^\[\]\n\\]|\\.)*)\](?:\[((?:[^\[\]\n\\]|\\.)*)\])?").unwrap()
+});
+/// `[label]: url "title"` on a line of its own.
+static DEFINITION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^ {0,3}\[((?:[^\[\]\n\\]|\\.)+)\]:\s*(\S+)(?:\s+("[^"\n]*"|'[^'\n]*'|\([^)\n]*\)))?\s*$"#)
+        .unwrap()
+});
+
+struct Definition {
+    line: usize,
+    label: String,
+    target: String,
+}
+
+/// Rewrite every link in `to` style. Returns `None` when nothing changed.
+pub fn convert(input: &str, to: LinkStyle) -> Option<String> {
+    match to {
+        LinkStyle::Reference => to_reference(input),
+        LinkStyle::Inline => to_inline(input),
+    }
+}
+
+fn to_reference(input: &str) -> Option<String> {
+    let existing = definitions(input);
+    let mut used: HashSet<String> = existing.iter().map(|d| normalize(&d.label)).collect();
+    let mut labels: HashMap<String, String> = existing
+        .iter()
+        .map(|d| (d.target.clone(), d.label.clone()))
+        .collect();
+    let mut added: Vec<(String, String)> = Vec::new();
+    let mut next = 1;
+
+    let body = map_prose(input, |text, out| {
+        let replaced = INLINE_RE.replace_all(text, |caps: &regex::Captures| {
+            let target = match caps.get(4) {
+                Some(title) => format!("{} {}", &caps[3], title.as_str()),
+                None => caps[3].to_string(),
+            };
+            let label = labels
+                .entry(target.clone())
+                .or_insert_with(|| {
+                    while used.contains(&next.to_string()) {
+                        next += 1;
+                    }
+                    let label = next.to_string();
+                    used.insert(label.clone());
+                    added.push((label.clone(), target));
+                    label
+                })
+                .clone();
+            format!("{}[{}][{}]", &caps[1], &caps[2], label)
+        });
+        out.push_str(&replaced);
+    });
+    if body == input {
+        return None;
+    }
+
+    let trailing_newline = input.ends_with('\n');
+    let body = body.trim_end();
+    let mut out = body.to_string();
+    let ends_with_definition = body
+        .lines()
+        .last()
+        .is_some_and(|line| DEFINITION_RE.is_match(line));
+    out.push_str(if ends_with_definition { "\n" } else { "\n\n" });
+    let lines: Vec<String> = added
+        .iter()
+        .map(|(label, target)| format!("[{}]: {}", label, target))
+        .collect();
+    out.push_str(&lines.join("\n"));
+    if trailing_newline {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn to_inline(input: &str) -> Option<String> {
+    let defs = definitions(input);
+    let targets: HashMap<String, &str> = defs
+        .iter()
+        .map(|d| (normalize(&d.label), d.target.as_str()))
+        .collect();
+    let mut used = HashSet::new();
+
+    let body = map_prose(input, |text, out| {
+        let replaced = REFERENCE_RE.replace_all(text, |caps: &regex::Captures| {
+            let whole = caps.get(0).unwrap();
+            // `[text](url)` is already inline and `[label]:` is a definition.
+            if caps.get(3).is_none() && text[whole.end()..].starts_with(['(', ':']) {
+                return whole.as_str().to_string();
+            }
+            let label = match caps.get(3) {
+                Some(label) if !label.as_str().is_empty() => normalize(label.as_str()),
+                _ => normalize(&caps[2]),
+            };
+            match targets.get(&label) {
+                Some(target) => {
+                    used.insert(label);
+                    format!("{}[{}]({})", &caps[1], &caps[2], target)
+                }
+                None => whole.as_str().to_string(),
+            }
+        });
+        out.push_str(&replaced);
+    });
+    if used.is_empty() {
+        return None;
+    }
+
+    // Drop the definitions that were inlined; unused ones stay.
+    let dropped: HashSet<usize> = defs
+        .iter()
+        .filter(|d| used.contains(&normalize(&d.label)))
+        .map(|d| d.line)
+        .collect();
+    let kept: Vec<&str> = body
+        .split('\n')
+        .enumerate()
+        .filter(|(i, _)| !dropped.contains(i))
+        .map(|(_, line)| line)
+        .collect();
+    let mut out = kept.join("\n").trim_end().to_string();
+    if input.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// Reference definitions outside fenced code, with their line numbers.
+fn definitions(input: &str) -> Vec<Definition> {
+    let mut fenced = false;
+    let mut defs = Vec::new();
+    for (line, text) in input.split('\n').enumerate() {
+        if text.trim_start().starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        let Some(caps) = DEFINITION_RE.captures(text).filter(|_| !fenced) else {
+            continue;
+        };
+        let target = match caps.get(3) {
+            Some(title) => format!("{} {}", &caps[2], title.as_str()),
+            None => caps[2].to_string(),
+        };
+        defs.push(Definition {
+            line,
+            label: caps[1].to_string(),
+            target,
+        });
+    }
+    defs
+}
+
+/// Labels match ignoring case and runs of whitespace.
+fn normalize(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
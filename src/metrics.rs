@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Upper bounds, in seconds, of the transform latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 8] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.1, 0.5];
+
+/// Per-rule counters since launch, served by the local API.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    rules: BTreeMap<String, RuleMetrics>,
+}
+
+type Counter = fn(&RuleMetrics) -> u64;
+
+#[derive(Debug, Default)]
+struct RuleMetrics {
+    invocations: u64,
+    failures: u64,
+    applied: u64,
+    /// Non-cumulative counts per bucket; the last slot is `+Inf`.
+    latency_counts: [u64; LATENCY_BUCKETS.len() + 1],
+    latency_sum: f64,
+}
+
+impl Metrics {
+    /// Record one transform run of `rule_id`.
+    pub fn record_run(&mut self, rule_id: &str, elapsed: Duration, ok: bool) {
+        let entry = self.rules.entry(rule_id.to_string()).or_default();
+        entry.invocations += 1;
+        if !ok {
+            entry.failures += 1;
+        }
+        let seconds = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        entry.latency_counts[bucket] += 1;
+        entry.latency_sum += seconds;
+    }
+
+    /// Record that `rule_id`'s output was pasted or copied.
+    pub fn record_applied(&mut self, rule_id: &str) {
+        self.rules.entry(rule_id.to_string()).or_default().applied += 1;
+    }
+
+    /// Prometheus text exposition format (version 0.0.4).
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let counters: [(&str, &str, Counter); 3] = [
+            (
+                "pasteflow_rule_invocations_total",
+                "Transform runs per rule.",
+                |m| m.invocations,
+            ),
+            (
+                "pasteflow_rule_failures_total",
+                "Transform runs that returned an error.",
+                |m| m.failures,
+            ),
+            (
+                "pasteflow_rule_applied_total",
+                "Rule outputs pasted or copied.",
+                |m| m.applied,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (id, metrics) in &self.rules {
+                let _ = writeln!(
+                    out,
+                    "{}{{rule=\"{}\"}} {}",
+                    name,
+                    escape(id),
+                    value(metrics)
+                );
+            }
+        }
+
+        let name = "pasteflow_rule_latency_seconds";
+        let _ = writeln!(out, "# HELP {} Transform run time per rule.", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (id, metrics) in &self.rules {
+            let id = escape(id);
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(&metrics.latency_counts) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{{rule=\"{}\",le=\"{}\"}} {}",
+                    name, id, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}_bucket{{rule=\"{}\",le=\"+Inf\"}} {}",
+                name, id, metrics.invocations
+            );
+            let _ = writeln!(
+                out,
+                "{}_sum{{rule=\"{}\"}} {}",
+                name, id, metrics.latency_sum
+            );
+            let _ = writeln!(
+                out,
+                "{}_count{{rule=\"{}\"}} {}",
+                name, id, metrics.invocations
+            );
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let rules: serde_json::Map<String, serde_json::Value> = self
+            .rules
+            .iter()
+            .map(|(id, metrics)| {
+                let mut cumulative = 0;
+                let mut buckets: Vec<serde_json::Value> = LATENCY_BUCKETS
+                    .iter()
+                    .zip(&metrics.latency_counts)
+                    .map(|(bound, count)| {
+                        cumulative += count;
+                        serde_json::json!({ "le": bound, "count": cumulative })
+                    })
+                    .collect();
+                buckets.push(serde_json::json!({ "le": "+Inf", "count": metrics.invocations }));
+                let value = serde_json::json!({
+                    "invocations": metrics.invocations,
+                    "failures": metrics.failures,
+                    "applied": metrics.applied,
+                    "latency_seconds": {
+                        "buckets": buckets,
+                        "sum": metrics.latency_sum,
+                        "count": metrics.invocations,
+                    },
+                });
+                (id.clone(), value)
+            })
+            .collect();
+        serde_json::json!({ "rules": rules })
+    }
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metrics;
+    use std::time::Duration;
+
+    #[test]
+    fn exports_counters_and_cumulative_buckets() {
+        let mut metrics = Metrics::default();
+        metrics.record_run("json_prettify", Duration::from_micros(300), true);
+        metrics.record_run("json_prettify", Duration::from_millis(2), false);
+        metrics.record_applied("json_prettify");
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("pasteflow_rule_invocations_total{rule=\"json_prettify\"} 2"));
+        assert!(text.contains("pasteflow_rule_failures_total{rule=\"json_prettify\"} 1"));
+        assert!(text.contains("pasteflow_rule_applied_total{rule=\"json_prettify\"} 1"));
+        assert!(text.contains(
+            "pasteflow_rule_latency_seconds_bucket{rule=\"json_prettify\",le=\"0.001\"} 1"
+        ));
+        assert!(text.contains(
+            "pasteflow_rule_latency_seconds_bucket{rule=\"json_prettify\",le=\"0.0025\"} 2"
+        ));
+
+        let json = metrics.to_json();
+        assert_eq!(json["rules"]["json_prettify"]["failures"], 1);
+        assert_eq!(
+            json["rules"]["json_prettify"]["latency_seconds"]["count"],
+            2
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Numbers and amounts as a locale writes them, `1.234,56 €` in German or
+//! `$1,234.56` in English, to and from plain `1234.56`.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Currency codes read as part of an amount, such as `100 USD` or `CHF 12.50`.
+const CURRENCY_CODES: &str =
+    "AUD|BRL|CAD|CHF|CNY|CZK|DKK|EUR|GBP|HKD|INR|JPY|KRW|MXN|NOK|NZD|PLN|RUB|SEK|SGD|TRY|USD|ZAR";
+
+/// `1234.56` or `-12`: group 1 is the integer part, 2 the fraction.
+static PLAIN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b([0-9]+)(?:\.([0-9]+))?\b").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Thousands separator.
+    group: char,
+    decimal: char,
+    /// `$1.00` rather than `1.00 $`.
+    currency_first: bool,
+    /// Space between a leading currency and the amount, as in `€ 1.234,56`.
+    currency_space: bool,
+}
+
+/// The number format of `locale` (`"en"`, `"de"`, `"de-CH"`, `"fr"`, ...).
+pub fn number_format(locale: &str) -> Option<NumberFormat> {
+    let locale = locale.trim().to_lowercase().replace('_', "-");
+    let language = locale.split('-').next().unwrap_or("");
+    let (group, decimal, currency_first, currency_space) = match (locale.as_str(), language) {
+        ("de-ch" | "de-li" | "fr-ch" | "it-ch", _) => ('’', '.', true, true),
+        ("pt-br", _) => ('.', ',', true, true),
+        (_, "en" | "ja" | "zh" | "ko" | "he" | "th") => (',', '.', true, false),
+        (_, "nl") => ('.', ',', true, true),
+        (_, "de" | "es" | "it" | "pt" | "da" | "el" | "id" | "tr") => ('.', ',', false, false),
+        (_, "fr") => ('\u{202f}', ',', false, false),
+        (_, "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no") => {
+            ('\u{a0}', ',', false, false)
+        }
+        _ => return None,
+    };
+    Some(NumberFormat {
+        group,
+        decimal,
+        currency_first,
+        currency_space,
+    })
+}
+
+/// Read every number and amount written in `format` as a plain number, dropping
+/// currency symbols and codes. Returns `None` when nothing changed.
+pub fn parse(input: &str, format: NumberFormat) -> Option<String> {
+    // Space-grouping locales are often pasted with plain or no-break spaces,
+    // and Swiss ones with a straight apostrophe.
+    let groups = match format.group {
+        ' ' | '\u{a0}' | '\u{202f}' => "[ \u{a0}\u{202f}]".to_string(),
+        '’' => "['’]".to_string(),
+        group => regex::escape(&group.to_string()),
+    };
+    let re = Regex::new(&format!(
+        r"([-−])?(?:(\p{{Sc}}|\b(?:{codes})\b)[ \u{{a0}}\u{{202f}}]?)?([-−])?\b([0-9]{{1,3}}(?:{groups}[0-9]{{3}})+|[0-9]+)(?:{decimal}([0-9]+))?\b(?:[ \u{{a0}}\u{{202f}}]?(\p{{Sc}}|\b(?:{codes})\b))?",
+        codes = CURRENCY_CODES,
+        groups = groups,
+        decimal = regex::escape(&format.decimal.to_string()),
+    ))
+    .ok()?;
+    let mut changed = false;
+    let out = re.replace_all(input, |caps: &regex::Captures| {
+        let whole = &caps[0];
+        let negative = caps.get(1).or(caps.get(3)).is_some();
+        let mut plain = String::new();
+        if negative {
+            plain.push('-');
+        }
+        plain.extend(caps[4].chars().filter(char::is_ascii_digit));
+        if let Some(fraction) = caps.get(5) {
+            plain.push('.');
+            plain.push_str(fraction.as_str());
+        }
+        if plain != whole {
+            changed = true;
+        }
+        plain
+    });
+    changed.then(|| out.into_owned())
+}
+
+/// Write every plain number the way `format` does, with `currency` placed where
+/// the locale puts it. Whole numbers under 10000 stay ungrouped unless they are
+/// amounts, so years and counts read naturally. Returns `None` when there are
+/// no plain numbers.
+pub fn localize(input: &str, format: NumberFormat, currency: Option<&str>) -> Option<String> {
+    let mut found = false;
+    let out = PLAIN_RE.replace_all(input, |caps: &regex::Captures| {
+        let whole = caps.get(0).unwrap();
+        let before = input[..whole.start()].chars().next_back();
+        let mut after = input[whole.end()..].chars();
+        let joined_after = matches!(after.next(), Some('.' | ',' | ':' | '/' | '-'))
+            && after.next().is_some_and(|ch| ch.is_ascii_digit());
+        let integer = &caps[1];
+        // Leave versions, addresses, dates, times, and zero-padded IDs.
+        if matches!(before, Some('.' | ',' | ':' | '/'))
+            || joined_after
+            || before == Some('-') && !is_sign(input, whole.start())
+            || integer.len() > 1 && integer.starts_with('0')
+        {
+            return whole.as_str().to_string();
+        }
+        found = true;
+        let mut number = if caps.get(2).is_some() || currency.is_some() || integer.len() > 4 {
+            group(integer, format.group)
+        } else {
+            integer.to_string()
+        };
+        if let Some(fraction) = caps.get(2) {
+            number.push(format.decimal);
+            number.push_str(fraction.as_str());
+        }
+        match currency {
+            Some(currency) if format.currency_first && format.currency_space => {
+                format!("{}\u{a0}{}", currency, number)
+            }
+            Some(currency) if format.currency_first => format!("{}{}", currency, number),
+            Some(currency) => format!("{}\u{a0}{}", number, currency),
+            None => number,
+        }
+    });
+    found.then(|| out.into_owned())
+}
+
+/// Whether the `-` before `start` is a minus sign rather than a range or date dash.
+fn is_sign(input: &str, start: usize) -> bool {
+    input[..start - 1]
+        .chars()
+        .next_back()
+        .is_none_or(|ch| !ch.is_alphanumeric())
+}
+
+fn group(digits: &str, separator: char) -> String {
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 * separator.len_utf8());
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(separator);
+        }
+        out.push(ch);
+    }
+    out
+}
@@ -0,0 +1,400 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Small delays ensure key events are processed in order.
+const KEY_DELAY: Duration = Duration::from_millis(10);
+
+/// Which backend sends the paste shortcut after the clipboard is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteBackendKind {
+    #[default]
+    Enigo,
+    /// Post `Cmd+V` through Quartz event services (macOS).
+    CgEvent,
+    /// Insert into the focused text element through the Accessibility API without
+    /// touching the clipboard, falling back to `cg_event` (macOS).
+    Accessibility,
+    /// Post `Ctrl+V` through `SendInput` (Windows).
+    SendInput,
+    /// Only write the clipboard; the user pastes manually.
+    ClipboardOnly,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PasteError {
+    #[error("paste backend '{0}' is not available on this platform")]
+    Unsupported(&'static str),
+    #[error("failed to send paste keystroke: {0}")]
+    #[cfg_attr(not(any(target_os = "macos", windows)), allow(dead_code))]
+    Send(String),
+}
+
+/// Sends the platform paste shortcut to the frontmost app.
+pub trait PasteBackend {
+    /// Insert `text` straight into the focused element of process `target`.
+    ///
+    /// Returns `Ok(false)` when the backend or the element can't take direct input;
+    /// the caller then writes the clipboard and calls `paste`.
+    fn insert_text(&mut self, _text: &str, _target: Option<u64>) -> Result<bool, PasteError> {
+        Ok(false)
+    }
+
+    fn paste(&mut self) -> Result<(), PasteError>;
+
+    /// Whether `paste` sends keystrokes (and so must not interrupt an IME composition).
+    fn sends_keystrokes(&self) -> bool {
+        true
+    }
+}
+
+pub fn backend(kind: PasteBackendKind) -> Result<Box<dyn PasteBackend>, PasteError> {
+    match kind {
+        PasteBackendKind::Enigo => Ok(Box::new(EnigoPaste)),
+        PasteBackendKind::CgEvent if cfg!(target_os = "macos") => Ok(Box::new(CgEventPaste)),
+        PasteBackendKind::CgEvent => Err(PasteError::Unsupported("cg_event")),
+        PasteBackendKind::Accessibility if cfg!(target_os = "macos") => {
+            Ok(Box::new(AccessibilityPaste))
+        }
+        PasteBackendKind::Accessibility => Err(PasteError::Unsupported("accessibility")),
+        PasteBackendKind::SendInput if cfg!(windows) => Ok(Box::new(SendInputPaste)),
+        PasteBackendKind::SendInput => Err(PasteError::Unsupported("send_input")),
+        PasteBackendKind::ClipboardOnly => Ok(Box::new(ClipboardOnlyPaste)),
+    }
+}
+
+struct EnigoPaste;
+
+impl PasteBackend for EnigoPaste {
+    fn paste(&mut self) -> Result<(), PasteError> {
+        use enigo::{Enigo, Key, KeyboardControllable};
+
+        let modifier = if cfg!(target_os = "macos") {
+            Key::Meta
+        } else {
+            Key::Control
+        };
+        // enigo 0.1 doesn't return errors
+        let mut enigo = Enigo::new();
+        enigo.key_down(modifier);
+        std::thread::sleep(KEY_DELAY);
+        enigo.key_click(Key::Layout('v'));
+        std::thread::sleep(KEY_DELAY);
+        enigo.key_up(modifier);
+        Ok(())
+    }
+}
+
+struct CgEventPaste;
+
+impl PasteBackend for CgEventPaste {
+    fn paste(&mut self) -> Result<(), PasteError> {
+        #[cfg(target_os = "macos")]
+        return cg_event::post_cmd_v().map_err(PasteError::Send);
+        #[cfg(not(target_os = "macos"))]
+        Err(PasteError::Unsupported("cg_event"))
+    }
+}
+
+struct AccessibilityPaste;
+
+impl PasteBackend for AccessibilityPaste {
+    fn insert_text(&mut self, text: &str, target: Option<u64>) -> Result<bool, PasteError> {
+        #[cfg(target_os = "macos")]
+        return ax::insert_selected_text(text, target).map_err(PasteError::Send);
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = (text, target);
+            Err(PasteError::Unsupported("accessibility"))
+        }
+    }
+
+    fn paste(&mut self) -> Result<(), PasteError> {
+        CgEventPaste.paste()
+    }
+}
+
+struct SendInputPaste;
+
+impl PasteBackend for SendInputPaste {
+    fn paste(&mut self) -> Result<(), PasteError> {
+        #[cfg(windows)]
+        return send_input::post_ctrl_v().map_err(PasteError::Send);
+        #[cfg(not(windows))]
+        Err(PasteError::Unsupported("send_input"))
+    }
+}
+
+struct ClipboardOnlyPaste;
+
+impl PasteBackend for ClipboardOnlyPaste {
+    fn paste(&mut self) -> Result<(), PasteError> {
+        Ok(())
+    }
+
+    fn sends_keystrokes(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod cg_event {
+    use super::KEY_DELAY;
+    use std::ffi::c_void;
+
+    type CGEventRef = *mut c_void;
+
+    const HID_EVENT_TAP: u32 = 0;
+    const FLAG_MASK_COMMAND: u64 = 0x0010_0000;
+    const KEYCODE_ANSI_V: u16 = 0x09;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    unsafe extern "C" {
+        fn CGEventCreateKeyboardEvent(
+            source: *const c_void,
+            keycode: u16,
+            key_down: bool,
+        ) -> CGEventRef;
+        fn CGEventSetFlags(event: CGEventRef, flags: u64);
+        fn CGEventPost(tap: u32, event: CGEventRef);
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFRelease(cf: *const c_void);
+    }
+
+    pub fn post_cmd_v() -> Result<(), String> {
+        for key_down in [true, false] {
+            // SAFETY: a null source is allowed; the event is released after posting.
+            unsafe {
+                let event = CGEventCreateKeyboardEvent(std::ptr::null(), KEYCODE_ANSI_V, key_down);
+                if event.is_null() {
+                    return Err("could not create keyboard event".to_string());
+                }
+                CGEventSetFlags(event, FLAG_MASK_COMMAND);
+                CGEventPost(HID_EVENT_TAP, event);
+                CFRelease(event);
+            }
+            std::thread::sleep(KEY_DELAY);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod ax {
+    use std::ffi::c_void;
+    use std::ptr;
+
+    type CFTypeRef = *const c_void;
+
+    const AX_SUCCESS: i32 = 0;
+    const STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    unsafe extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+        fn AXUIElementCreateSystemWide() -> CFTypeRef;
+        fn AXUIElementCreateApplication(pid: i32) -> CFTypeRef;
+        fn AXUIElementCopyAttributeValue(
+            element: CFTypeRef,
+            attribute: CFTypeRef,
+            value: *mut CFTypeRef,
+        ) -> i32;
+        fn AXUIElementIsAttributeSettable(
+            element: CFTypeRef,
+            attribute: CFTypeRef,
+            settable: *mut u8,
+        ) -> i32;
+        fn AXUIElementSetAttributeValue(
+            element: CFTypeRef,
+            attribute: CFTypeRef,
+            value: CFTypeRef,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFStringCreateWithBytes(
+            alloc: CFTypeRef,
+            bytes: *const u8,
+            len: isize,
+            encoding: u32,
+            external: bool,
+        ) -> CFTypeRef;
+        fn CFEqual(a: CFTypeRef, b: CFTypeRef) -> u8;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    /// Owned Core Foundation object, released on drop.
+    struct Owned(CFTypeRef);
+
+    impl Drop for Owned {
+        fn drop(&mut self) {
+            if !self.0.is_null() {
+                // SAFETY: `Owned` only wraps objects returned by Create/Copy functions.
+                unsafe { CFRelease(self.0) }
+            }
+        }
+    }
+
+    fn cf_string(text: &str) -> Owned {
+        // SAFETY: the bytes are valid UTF-8 for the duration of the call.
+        Owned(unsafe {
+            CFStringCreateWithBytes(
+                ptr::null(),
+                text.as_ptr(),
+                text.len() as isize,
+                STRING_ENCODING_UTF8,
+                false,
+            )
+        })
+    }
+
+    /// The value of `attribute` on `element`, if it has one.
+    fn copy_attribute(element: &Owned, attribute: &str) -> Option<Owned> {
+        let attribute = cf_string(attribute);
+        let mut value: CFTypeRef = ptr::null();
+        // SAFETY: `element` and `attribute` are live; a copied value is owned.
+        let status = unsafe { AXUIElementCopyAttributeValue(element.0, attribute.0, &mut value) };
+        let value = Owned(value);
+        (status == AX_SUCCESS && !value.0.is_null()).then_some(value)
+    }
+
+    /// Replace the selection (or insert at the caret) of the focused element.
+    /// `Ok(true)` only once the element's contents are seen to change: some
+    /// apps accept the write and ignore it, and those need the keystroke path.
+    pub fn insert_selected_text(text: &str, target: Option<u64>) -> Result<bool, String> {
+        // SAFETY: every reference passed to AX is either owned here or null-checked.
+        unsafe {
+            if !AXIsProcessTrusted() {
+                return Ok(false);
+            }
+            let root = match target.and_then(|pid| i32::try_from(pid).ok()) {
+                Some(pid) => Owned(AXUIElementCreateApplication(pid)),
+                None => Owned(AXUIElementCreateSystemWide()),
+            };
+            let focused_attr = cf_string("AXFocusedUIElement");
+            let mut focused: CFTypeRef = ptr::null();
+            if AXUIElementCopyAttributeValue(root.0, focused_attr.0, &mut focused) != AX_SUCCESS
+                || focused.is_null()
+            {
+                return Ok(false);
+            }
+            let focused = Owned(focused);
+            let selected_attr = cf_string("AXSelectedText");
+            let mut settable = 0u8;
+            if AXUIElementIsAttributeSettable(focused.0, selected_attr.0, &mut settable)
+                != AX_SUCCESS
+                || settable == 0
+            {
+                return Ok(false);
+            }
+            // Without a readable value or length the insertion can't be
+            // confirmed, so leave such fields to the keystroke path.
+            let Some((probe, before)) = ["AXValue", "AXNumberOfCharacters"]
+                .into_iter()
+                .find_map(|name| copy_attribute(&focused, name).map(|value| (name, value)))
+            else {
+                return Ok(false);
+            };
+            let value = cf_string(text);
+            if value.0.is_null() {
+                return Err("could not create string".to_string());
+            }
+            match AXUIElementSetAttributeValue(focused.0, selected_attr.0, value.0) {
+                AX_SUCCESS => {}
+                code => return Err(format!("AXError {}", code)),
+            }
+            let after = copy_attribute(&focused, probe);
+            Ok(after.is_some_and(|after| CFEqual(before.0, after.0) == 0))
+        }
+    }
+}
+
+#[cfg(windows)]
+mod send_input {
+    const INPUT_KEYBOARD: u32 = 1;
+    const KEYEVENTF_KEYUP: u32 = 0x0002;
+    const VK_CONTROL: u16 = 0x11;
+    const VK_V: u16 = 0x56;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    #[allow(dead_code)]
+    struct MouseInput {
+        dx: i32,
+        dy: i32,
+        mouse_data: u32,
+        flags: u32,
+        time: u32,
+        extra_info: usize,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct KeybdInput {
+        vk: u16,
+        scan: u16,
+        flags: u32,
+        time: u32,
+        extra_info: usize,
+    }
+
+    /// `INPUT.u` is sized by its largest member, `MOUSEINPUT`.
+    #[repr(C)]
+    #[allow(dead_code)]
+    union InputUnion {
+        mi: MouseInput,
+        ki: KeybdInput,
+    }
+
+    #[repr(C)]
+    struct Input {
+        kind: u32,
+        u: InputUnion,
+    }
+
+    #[link(name = "user32")]
+    unsafe extern "system" {
+        fn SendInput(count: u32, inputs: *const Input, size: i32) -> u32;
+    }
+
+    fn key(vk: u16, up: bool) -> Input {
+        Input {
+            kind: INPUT_KEYBOARD,
+            u: InputUnion {
+                ki: KeybdInput {
+                    vk,
+                    scan: 0,
+                    flags: if up { KEYEVENTF_KEYUP } else { 0 },
+                    time: 0,
+                    extra_info: 0,
+                },
+            },
+        }
+    }
+
+    pub fn post_ctrl_v() -> Result<(), String> {
+        let inputs = [
+            key(VK_CONTROL, false),
+            key(VK_V, false),
+            key(VK_V, true),
+            key(VK_CONTROL, true),
+        ];
+        // SAFETY: `inputs` is a valid array of `INPUT` structs for the duration of the call.
+        let sent = unsafe {
+            SendInput(
+                inputs.len() as u32,
+                inputs.as_ptr(),
+                std::mem::size_of::<Input>() as i32,
+            )
+        };
+        if sent as usize == inputs.len() {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        }
+    }
+}
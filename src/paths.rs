@@ -0,0 +1,250 @@
+//! Conversion between Windows, POSIX, and `file://` URL forms of a path.
+
+use crate::transforms::PathStyle;
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
+
+/// Bytes escaped in a `file://` path segment.
+const URL_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'/')
+    .add(b'\\');
+
+/// Characters a POSIX shell needs backslash-escaped.
+const SHELL_SPECIAL: &str = " \t'\"\\()[]{}<>&;|$`!*?#";
+
+/// A path split into its root and components, independent of style.
+#[derive(Debug, PartialEq, Eq)]
+struct ParsedPath {
+    root: Root,
+    parts: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Root {
+    Relative,
+    /// `/` on POSIX, or the root of the current drive on Windows.
+    Absolute,
+    /// A Windows drive letter; `/mnt/c` on POSIX (WSL).
+    Drive(char),
+    /// A network host, as in `\\server\share` or `file://server/share`.
+    Host(String),
+}
+
+/// Convert every non-blank line of `input`, each holding one path.
+pub fn convert(input: &str, to: PathStyle) -> Result<String, String> {
+    let mut out = Vec::new();
+    for line in input.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            out.push(String::new());
+            continue;
+        }
+        let path = parse(trimmed)?;
+        out.push(match to {
+            PathStyle::Posix => to_posix(&path),
+            PathStyle::Windows => to_windows(&path),
+            PathStyle::FileUrl => to_file_url(&path).ok_or_else(|| {
+                format!("'{}' is relative; file URLs need an absolute path", trimmed)
+            })?,
+        });
+    }
+    Ok(out.join("\n"))
+}
+
+fn parse(input: &str) -> Result<ParsedPath, String> {
+    let path = strip_quotes(input);
+    if path
+        .get(..7)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file://"))
+    {
+        parse_file_url(path)
+    } else if is_windows(path) {
+        Ok(parse_windows(path))
+    } else {
+        Ok(parse_posix(path))
+    }
+}
+
+fn strip_quotes(input: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = input
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    input
+}
+
+fn drive_letter(path: &str) -> Option<char> {
+    let mut chars = path.chars();
+    let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+    (chars.next() == Some(':') && chars.next().is_none_or(|c| c == '\\' || c == '/'))
+        .then(|| letter.to_ascii_uppercase())
+}
+
+fn is_windows(path: &str) -> bool {
+    drive_letter(path).is_some()
+        || path.starts_with("\\\\")
+        // `a\ b` is a shell-escaped POSIX path, not a Windows one.
+        || (path.contains('\\') && !path.contains('/') && !path.contains("\\ "))
+}
+
+fn split_parts(path: &str, separators: &[char]) -> Vec<String> {
+    path.split(separators)
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_windows(path: &str) -> ParsedPath {
+    let separators = ['\\', '/'];
+    if let Some(rest) = path.strip_prefix("\\\\") {
+        let (host, rest) = rest.split_once(separators).unwrap_or((rest, ""));
+        return ParsedPath {
+            root: Root::Host(host.to_string()),
+            parts: split_parts(rest, &separators),
+        };
+    }
+    let (root, rest) = match drive_letter(path) {
+        Some(letter) => (Root::Drive(letter), &path[2..]),
+        None if path.starts_with(separators) => (Root::Absolute, path),
+        None => (Root::Relative, path),
+    };
+    ParsedPath {
+        root,
+        parts: split_parts(rest, &separators),
+    }
+}
+
+fn parse_posix(path: &str) -> ParsedPath {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => unescaped.extend(chars.next()),
+            ch => unescaped.push(ch),
+        }
+    }
+    let parts = split_parts(&unescaped, &['/']);
+    let root = if let Some(rest) = unescaped.strip_prefix("//") {
+        Root::Host(rest.split('/').next().unwrap_or("").to_string())
+    } else if unescaped.starts_with('/') {
+        Root::Absolute
+    } else {
+        Root::Relative
+    };
+    match root {
+        Root::Host(_) => ParsedPath {
+            root,
+            parts: parts.iter().skip(1).cloned().collect(),
+        },
+        // WSL mounts Windows drives under `/mnt/<letter>`.
+        Root::Absolute
+            if parts.len() >= 2
+                && parts[0] == "mnt"
+                && parts[1].len() == 1
+                && parts[1].chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            ParsedPath {
+                root: Root::Drive(parts[1].to_ascii_uppercase().chars().next().unwrap_or('C')),
+                parts: parts[2..].to_vec(),
+            }
+        }
+        root => ParsedPath { root, parts },
+    }
+}
+
+fn parse_file_url(input: &str) -> Result<ParsedPath, String> {
+    let url = url::Url::parse(input).map_err(|e| format!("invalid file URL: {}", e))?;
+    let mut parts: Vec<String> = url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect();
+    let root = match url.host_str() {
+        Some(host) if !host.is_empty() && host != "localhost" => Root::Host(host.to_string()),
+        _ => match parts
+            .first()
+            .and_then(|first| drive_letter(&first.replace('|', ":")))
+        {
+            Some(letter) => {
+                parts.remove(0);
+                Root::Drive(letter)
+            }
+            None => Root::Absolute,
+        },
+    };
+    Ok(ParsedPath { root, parts })
+}
+
+fn to_posix(path: &ParsedPath) -> String {
+    let parts: Vec<String> = path
+        .parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            // A leading `~` still means the home directory.
+            if i == 0 && part == "~" && path.root == Root::Relative {
+                return part.clone();
+            }
+            let mut escaped = String::with_capacity(part.len());
+            for ch in part.chars() {
+                if SHELL_SPECIAL.contains(ch) {
+                    escaped.push('\\');
+                }
+                escaped.push(ch);
+            }
+            escaped
+        })
+        .collect();
+    let joined = parts.join("/");
+    match &path.root {
+        Root::Relative => joined,
+        Root::Absolute => format!("/{}", joined),
+        Root::Drive(letter) => format!("/mnt/{}/{}", letter.to_ascii_lowercase(), joined),
+        Root::Host(host) => format!("//{}/{}", host, joined),
+    }
+}
+
+fn to_windows(path: &ParsedPath) -> String {
+    let joined = path.parts.join("\\");
+    let out = match &path.root {
+        Root::Relative => joined,
+        Root::Absolute => format!("\\{}", joined),
+        Root::Drive(letter) => format!("{}:\\{}", letter, joined),
+        Root::Host(host) => format!("\\\\{}\\{}", host, joined),
+    };
+    if out.contains(' ') {
+        format!("\"{}\"", out)
+    } else {
+        out
+    }
+}
+
+fn to_file_url(path: &ParsedPath) -> Option<String> {
+    let encoded: Vec<String> = path
+        .parts
+        .iter()
+        .map(|part| utf8_percent_encode(part, URL_SEGMENT).to_string())
+        .collect();
+    let joined = encoded.join("/");
+    match &path.root {
+        Root::Relative => None,
+        Root::Absolute => Some(format!("file:///{}", joined)),
+        Root::Drive(letter) => Some(format!("file:///{}:/{}", letter, joined)),
+        Root::Host(host) => Some(format!("file://{}/{}", host, joined)),
+    }
+}
@@ -0,0 +1,167 @@
+//! WebAssembly plugins from `~/.config/pasteflow/plugins/*.wasm`, run with
+//! `TransformKind::Plugin { name }`. A plugin gets no imports, so it cannot
+//! reach files, the network, or the clock, and runs under fuel and memory limits.
+//!
+//! ABI: the module exports `memory`, `alloc(len: i32) -> i32`, which returns
+//! where the host may write `len` bytes, and `transform(ptr: i32, len: i32) -> i64`,
+//! which reads UTF-8 from `ptr` and returns its output as `(out_ptr << 32) | out_len`.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Instructions (roughly) a plugin may execute per call.
+const FUEL: u64 = 100_000_000;
+/// Largest linear memory a plugin may grow to.
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+static ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    Engine::new(&config)
+});
+
+/// A compiled module and the modification time of the file it came from.
+type Compiled = (SystemTime, Arc<Module>);
+
+/// Compiled modules by path, recompiled when the file changes.
+static MODULES: Lazy<Mutex<HashMap<PathBuf, Compiled>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn plugin_dir() -> PathBuf {
+    let mut root = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    root.push(".config");
+    root.push("pasteflow");
+    root.push("plugins");
+    root
+}
+
+/// Names of the installed plugins, sorted.
+pub fn discover() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(plugin_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Run the plugin `name` on `input`.
+pub fn run(name: &str, input: &str) -> Result<String, String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("invalid plugin name '{}'", name));
+    }
+    let path = plugin_dir().join(format!("{}.wasm", name));
+    let modified = fs::metadata(&path)
+        .and_then(|meta| meta.modified())
+        .map_err(|_| format!("plugin '{}' not found in {}", name, plugin_dir().display()))?;
+
+    let mut modules = MODULES.lock().unwrap_or_else(|e| e.into_inner());
+    let module = match modules.get(&path) {
+        Some((at, module)) if *at == modified => module.clone(),
+        _ => {
+            let bytes = fs::read(&path).map_err(|e| format!("plugin '{}': {}", name, e))?;
+            let module = compile(&bytes).map_err(|e| format!("plugin '{}': {}", name, e))?;
+            let module = Arc::new(module);
+            modules.insert(path, (modified, module.clone()));
+            module
+        }
+    };
+    drop(modules);
+    call(&module, input).map_err(|e| format!("plugin '{}': {}", name, e))
+}
+
+fn compile(bytes: &[u8]) -> Result<Module, String> {
+    Module::new(&ENGINE, bytes).map_err(|e| format!("invalid module: {}", e))
+}
+
+fn call(module: &Module, input: &str) -> Result<String, String> {
+    let limits = StoreLimitsBuilder::new()
+        .memory_size(MAX_MEMORY_BYTES)
+        .build();
+    let mut store = Store::new(&ENGINE, limits);
+    store.limiter(|limits: &mut StoreLimits| limits);
+    store.set_fuel(FUEL).map_err(|e| e.to_string())?;
+
+    let instance = Linker::<StoreLimits>::new(&ENGINE)
+        .instantiate(&mut store, module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(|e| format!("cannot instantiate: {}", e))?;
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or("does not export `memory`")?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(|_| "does not export `alloc(i32) -> i32`")?;
+    let transform = instance
+        .get_typed_func::<(i32, i32), i64>(&store, "transform")
+        .map_err(|_| "does not export `transform(i32, i32) -> i64`")?;
+
+    let len = i32::try_from(input.len()).map_err(|_| "input is too large")?;
+    let ptr = alloc.call(&mut store, len).map_err(trap)?;
+    memory
+        .write(&mut store, ptr as u32 as usize, input.as_bytes())
+        .map_err(|_| "`alloc` returned memory out of bounds")?;
+    let packed = transform.call(&mut store, (ptr, len)).map_err(trap)? as u64;
+
+    let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    let mut output = vec![0; out_len];
+    memory
+        .read(&store, out_ptr, &mut output)
+        .map_err(|_| "`transform` returned memory out of bounds")?;
+    String::from_utf8(output).map_err(|_| "`transform` returned non-UTF-8 output".to_string())
+}
+
+fn trap(err: wasmi::Error) -> String {
+    if err
+        .as_trap_code()
+        .is_some_and(|code| code == wasmi::core::TrapCode::OutOfFuel)
+    {
+        "ran out of fuel".to_string()
+    } else {
+        format!("trapped: {}", err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call, compile};
+
+    const ALLOC: &str = r#"(memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) i32.const 1024)"#;
+
+    fn module(transform_body: &str) -> wasmi::Module {
+        let wat = format!(
+            "(module {} (func (export \"transform\") (param i32 i32) (result i64) {}))",
+            ALLOC, transform_body
+        );
+        compile(&wat::parse_str(wat).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn plugins_echo_and_are_stopped_when_out_of_fuel() {
+        let echo = module(
+            "(i64.or (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32))
+                     (i64.extend_i32_u (local.get 1)))",
+        );
+        assert_eq!(
+            call(&echo, "h\u{e9}llo plugin").unwrap(),
+            "h\u{e9}llo plugin"
+        );
+        assert_eq!(call(&echo, "").unwrap(), "");
+
+        let spin = module("(loop (br 0)) i64.const 0");
+        assert_eq!(call(&spin, "x").unwrap_err(), "ran out of fuel");
+        let out_of_bounds = module("i64.const 0x7fffffff00000010");
+        assert!(call(&out_of_bounds, "x").is_err());
+        assert!(compile(b"not wasm").is_err());
+    }
+}
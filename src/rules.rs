@@ -1,10 +1,16 @@
 use crate::detect::ContentType;
 use crate::transforms::TransformKind;
 use once_cell::sync::OnceCell;
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// Compiled size cap for `match.regex`, so a pathological pattern fails to
+/// compile instead of eating memory on every clipboard change.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+/// Matches returned by `test_regex`; the rest are only counted.
+const MAX_TEST_MATCHES: usize = 100;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rule {
     pub id: String,
@@ -13,12 +19,20 @@ pub struct Rule {
     pub description: Option<String>,
     #[serde(default)]
     pub pinned: bool,
-    #[serde(default)]
-    pub transform: Option<TransformKind>,
+    /// Transforms applied in order, each to the previous one's output.
+    #[serde(
+        default,
+        deserialize_with = "crate::transforms::deserialize_transforms",
+        serialize_with = "crate::transforms::serialize_transforms",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub transform: Vec<TransformKind>,
     #[serde(default)]
     pub llm: Option<LlmRule>,
     #[serde(default)]
     pub auto_accept: bool,
+    #[serde(default)]
+    pub privacy: Privacy,
     #[serde(rename = "match", default)]
     pub matchers: Matchers,
     /// Cached compiled regex (populated lazily, skipped in serialization)
@@ -26,12 +40,33 @@ pub struct Rule {
     compiled_regex: Arc<OnceCell<Option<Regex>>>,
 }
 
+/// How much of a rule's output Pasteflow may keep or expose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Privacy {
+    #[default]
+    Normal,
+    /// No history snippet, no metrics, and the output is masked in the panel
+    /// and local API once pasted or copied.
+    Sensitive,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Matchers {
     #[serde(default)]
     pub content_types: Option<Vec<ContentType>>,
+    /// Content types that rule the rule out, e.g. `sensitive` for an LLM rule.
+    #[serde(default)]
+    pub exclude_content_types: Option<Vec<ContentType>>,
+    /// Part of the app name, a bundle identifier such as `com.microsoft.VSCode`,
+    /// or a `*`/`?` glob over either, all ignoring case.
     #[serde(default)]
     pub apps: Option<Vec<String>>,
+    /// Sites the frontmost browser tab is on: a domain such as `github.com`,
+    /// which takes in its subdomains, a domain and path prefix such as
+    /// `github.com/rust-lang`, or a `*`/`?` glob over either.
+    #[serde(default)]
+    pub url: Option<Vec<String>>,
     #[serde(default)]
     pub regex: Option<String>,
 }
@@ -46,8 +81,25 @@ pub struct LlmRule {
 #[derive(Debug, Clone)]
 pub struct MatchContext {
     pub text: String,
-    pub content_types: Vec<ContentType>,
+    /// Detected content types, each with its confidence from 0 to 1.
+    pub content_types: Vec<(ContentType, f32)>,
     pub active_app: Option<String>,
+    /// Bundle identifier of the active app on macOS (`com.microsoft.VSCode`),
+    /// its executable name elsewhere. Unlike the name, it is not localized.
+    pub active_app_id: Option<String>,
+    /// URL of the active tab when the active app is a supported browser.
+    pub active_url: Option<String>,
+}
+
+impl MatchContext {
+    /// How sure detection is that the clip is `content_type`; `None` when it
+    /// wasn't detected.
+    pub fn confidence(&self, content_type: &ContentType) -> Option<f32> {
+        self.content_types
+            .iter()
+            .find(|(detected, _)| detected == content_type)
+            .map(|(_, confidence)| *confidence)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,9 +108,68 @@ pub struct Suggestion {
     pub score: i32,
 }
 
+/// Result of running a matcher regex against sample text. Offsets are UTF-16
+/// code units so the panel can slice JavaScript strings with them.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegexTest {
+    pub pattern: String,
+    pub matches: Vec<RegexMatch>,
+    /// Total matches, including any beyond the ones returned.
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegexMatch {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// Capture groups after the whole match; `None` when a group didn't take part.
+    pub groups: Vec<Option<RegexGroup>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegexGroup {
+    pub name: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl LlmRule {
+    /// Prompt with `{app}` replaced by the destination app name.
+    pub fn render_prompt(&self, app: Option<&str>) -> String {
+        self.prompt
+            .replace("{app}", app.unwrap_or("the destination app"))
+    }
+}
+
 impl Rule {
-    pub fn transform_kind(&self) -> Option<TransformKind> {
-        self.transform
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            description: None,
+            pinned: false,
+            transform: Vec::new(),
+            llm: None,
+            auto_accept: false,
+            privacy: Privacy::Normal,
+            matchers: Matchers::default(),
+            compiled_regex: Arc::default(),
+        }
+    }
+
+    pub fn transforms(&self) -> &[TransformKind] {
+        &self.transform
+    }
+
+    /// Whether applying this rule sends the clipboard to a model provider.
+    pub fn uses_remote(&self) -> bool {
+        self.llm.is_some() || self.transform.iter().any(TransformKind::uses_remote)
+    }
+
+    pub fn is_sensitive(&self) -> bool {
+        self.privacy == Privacy::Sensitive
     }
 
     /// Get the compiled regex, caching it for future calls
@@ -68,7 +179,7 @@ impl Rule {
                 self.matchers
                     .regex
                     .as_ref()
-                    .and_then(|pattern| Regex::new(pattern).ok())
+                    .and_then(|pattern| compile_regex(pattern).ok())
             })
             .as_ref()
     }
@@ -76,40 +187,46 @@ impl Rule {
     pub fn matches(&self, ctx: &MatchContext) -> Option<i32> {
         let mut score = 0;
         let mut specificity = 0;
+        if let Some(excluded) = &self.matchers.exclude_content_types
+            && excluded.iter().any(|t| ctx.confidence(t).is_some())
+        {
+            return None;
+        }
         if let Some(content_types) = &self.matchers.content_types {
-            let matched = content_types
+            let matched: Vec<f32> = content_types
                 .iter()
-                .filter(|t| ctx.content_types.contains(t))
-                .count();
-            if matched == 0 {
+                .filter_map(|t| ctx.confidence(t))
+                .collect();
+            if matched.is_empty() {
                 return None;
             }
-            score += 60 + (matched as i32 * 5);
+            // Scaled by confidence, so a rule for a near-certain type outranks
+            // one for a type the clip only loosely resembles.
+            let best = matched.iter().copied().fold(0.0, f32::max);
+            let total: f32 = matched.iter().sum();
+            score += (60.0 * best + 5.0 * total).round() as i32;
             specificity += 1;
         }
         if let Some(apps) = &self.matchers.apps {
-            let active = ctx.active_app.as_deref().unwrap_or("");
-            let active_lower = active.to_lowercase();
-            let mut matched = false;
-            let mut exact = false;
-            for app in apps {
-                let needle = app.to_lowercase();
-                if active_lower.contains(&needle) {
-                    matched = true;
-                    if active_lower == needle {
-                        exact = true;
-                    }
-                }
-            }
-            if !matched {
-                return None;
-            }
+            let exact = match_apps(
+                apps,
+                ctx.active_app.as_deref(),
+                ctx.active_app_id.as_deref(),
+            )?;
             score += 50;
             if exact {
                 score += 10;
             }
             specificity += 1;
         }
+        if let Some(sites) = &self.matchers.url {
+            let url = ctx.active_url.as_deref().unwrap_or("");
+            if !sites.iter().any(|site| url_matches(site, url)) {
+                return None;
+            }
+            score += 50;
+            specificity += 1;
+        }
         if self.matchers.regex.is_some() {
             // Use cached compiled regex
             let re = self.get_compiled_regex()?;
@@ -131,6 +248,138 @@ impl Rule {
     }
 }
 
+/// Whether `url` is on `site`, ignoring case, the scheme, and a leading
+/// `www.`. A site without a path matches its host and subdomains; one with a
+/// path matches URLs under it.
+fn url_matches(site: &str, url: &str) -> bool {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str() else {
+        return false;
+    };
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let site = site.trim().to_lowercase();
+    let site = site
+        .strip_prefix("https://")
+        .or_else(|| site.strip_prefix("http://"))
+        .unwrap_or(&site);
+    let site = site.strip_prefix("www.").unwrap_or(site);
+    let location = format!("{}{}", host, parsed.path().to_lowercase());
+    match (site.contains(['*', '?']), site.contains('/')) {
+        (true, true) => glob_matches(site, &location),
+        (true, false) => glob_matches(site, host),
+        (false, true) => location.starts_with(site.trim_end_matches('/')),
+        (false, false) => host == site || host.ends_with(&format!(".{}", site)),
+    }
+}
+
+/// How `patterns` match the app named `name` with identifier `id`: `None`
+/// when none does, `Some(true)` when one names it exactly. A pattern is part
+/// of the name, the whole identifier, or a `*`/`?` glob over either, all
+/// ignoring case. Used by `match.apps`, policies, and per-app hotkeys.
+pub fn match_apps(patterns: &[String], name: Option<&str>, id: Option<&str>) -> Option<bool> {
+    let name = name.unwrap_or("").to_lowercase();
+    let id = id.unwrap_or("").to_lowercase();
+    let mut matched = false;
+    let mut exact = false;
+    for pattern in patterns {
+        let needle = pattern.to_lowercase();
+        if needle.contains(['*', '?']) {
+            matched |= glob_matches(&needle, &name) || !id.is_empty() && glob_matches(&needle, &id);
+        } else if needle == name || needle == id {
+            matched = true;
+            exact = true;
+        } else if !name.is_empty() && name.contains(&needle) {
+            matched = true;
+        }
+    }
+    matched.then_some(exact)
+}
+
+/// Whether all of `text` matches `pattern`, where `*` stands for any run of
+/// characters and `?` for any one.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The last `*` seen and where in `text` its match ends so far.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Compile a `match.regex` pattern the way rule matching does.
+pub fn compile_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .build()
+}
+
+/// Run `pattern` over `sample` for the rule editor's regex tester.
+pub fn test_regex(pattern: &str, sample: &str) -> Result<RegexTest, String> {
+    let re = compile_regex(pattern).map_err(|err| err.to_string())?;
+    let names: Vec<Option<String>> = re
+        .capture_names()
+        .skip(1)
+        .map(|name| name.map(str::to_string))
+        .collect();
+    let utf16_offset = |byte: usize| sample[..byte].encode_utf16().count();
+    let mut matches = Vec::new();
+    let mut total = 0;
+    for caps in re.captures_iter(sample) {
+        total += 1;
+        if matches.len() == MAX_TEST_MATCHES {
+            continue;
+        }
+        let whole = caps.get(0).expect("group 0 always participates");
+        let groups = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                caps.get(index + 1).map(|group| RegexGroup {
+                    name: name.clone(),
+                    start: utf16_offset(group.start()),
+                    end: utf16_offset(group.end()),
+                    text: group.as_str().to_string(),
+                })
+            })
+            .collect();
+        matches.push(RegexMatch {
+            start: utf16_offset(whole.start()),
+            end: utf16_offset(whole.end()),
+            text: whole.as_str().to_string(),
+            groups,
+        });
+    }
+    Ok(RegexTest {
+        pattern: pattern.to_string(),
+        matches,
+        total,
+    })
+}
+
 pub fn suggest_rules(rules: &[Rule], ctx: &MatchContext, max: usize) -> Vec<Suggestion> {
     let mut suggestions: Vec<Suggestion> = rules
         .iter()
@@ -142,7 +391,202 @@ pub fn suggest_rules(rules: &[Rule], ctx: &MatchContext, max: usize) -> Vec<Sugg
         })
         .collect();
 
-    suggestions.sort_by(|a, b| b.score.cmp(&a.score));
+    suggestions.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.score));
     suggestions.truncate(max);
     suggestions
 }
+
+/// Rules to offer when `suggest_rules` finds nothing, in configured order.
+///
+/// Only rules of pure transforms whose pipeline succeeds on the text are kept,
+/// so a JSON rule in the list is offered only when the clipboard actually
+/// parses. Rules that run a program, script, plugin, or model are never tried.
+pub fn fallback_rules(rules: &[Rule], ids: &[String], text: &str, max: usize) -> Vec<Suggestion> {
+    ids.iter()
+        .filter_map(|id| rules.iter().find(|rule| &rule.id == id))
+        .filter(|rule| {
+            !rule.transform.is_empty()
+                && rule.transform.iter().all(TransformKind::is_pure)
+                && rule
+                    .transform
+                    .iter()
+                    .try_fold(text.to_string(), |current, kind| kind.apply(&current))
+                    .is_ok()
+        })
+        .take(max)
+        .map(|rule| Suggestion {
+            rule: rule.clone(),
+            score: 0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchContext, Rule, fallback_rules, test_regex, url_matches};
+    use crate::detect::{ContentType, detect_content_types};
+    use crate::transforms::TransformKind;
+
+    #[test]
+    fn regex_tester_reports_utf16_spans_and_groups() {
+        let result = test_regex(r"(?P<key>\w+)=(\d+)?", "é a=1 b=").unwrap();
+        assert_eq!(result.total, 2);
+        let first = &result.matches[0];
+        assert_eq!((first.start, first.end), (2, 5));
+        let key = first.groups[0].as_ref().unwrap();
+        assert_eq!(key.name.as_deref(), Some("key"));
+        assert_eq!(key.text, "a");
+        assert_eq!(first.groups[1].as_ref().unwrap().text, "1");
+        assert!(result.matches[1].groups[1].is_none());
+        assert!(test_regex("(", "text").is_err());
+    }
+
+    #[test]
+    fn confident_content_types_rank_first() {
+        let rule = |id: &str, content_type: &str| -> Rule {
+            toml::from_str(&format!(
+                "id = \"{id}\"\nname = \"{id}\"\n[match]\ncontent_types = [\"{content_type}\"]"
+            ))
+            .unwrap()
+        };
+        let (text_rule, yaml_rule) = (rule("text", "text"), rule("yaml", "yaml"));
+        let score = |rule: &Rule, text: &str| {
+            rule.matches(&MatchContext {
+                text: text.to_string(),
+                content_types: detect_content_types(text),
+                active_app: None,
+                active_app_id: None,
+                active_url: None,
+            })
+        };
+        let prose = "Reminder: the build is red, please take a look today";
+        assert!(score(&text_rule, prose) > score(&yaml_rule, prose));
+        let config = "name: web\nports:\n  - 80\n";
+        assert!(score(&yaml_rule, config) > score(&text_rule, config));
+
+        let exact = MatchContext {
+            text: String::new(),
+            content_types: vec![(ContentType::Json, 1.0)],
+            active_app: None,
+            active_app_id: None,
+            active_url: None,
+        };
+        assert_eq!(rule("json", "json").matches(&exact), Some(70));
+    }
+
+    #[test]
+    fn apps_match_names_bundle_ids_and_globs() {
+        let rule = |apps: &str| -> Rule {
+            toml::from_str(&format!(
+                "id = \"a\"\nname = \"A\"\n[match]\napps = {}",
+                apps
+            ))
+            .unwrap()
+        };
+        // A German system names the app "Code", but the bundle ID stays put.
+        let ctx = MatchContext {
+            text: String::new(),
+            content_types: Vec::new(),
+            active_app: Some("Code".to_string()),
+            active_app_id: Some("com.microsoft.VSCode".to_string()),
+            active_url: None,
+        };
+        assert_eq!(rule("[\"com.microsoft.vscode\"]").matches(&ctx), Some(65));
+        assert_eq!(rule("[\"Code\"]").matches(&ctx), Some(65));
+        assert_eq!(rule("[\"co\"]").matches(&ctx), Some(55));
+        assert_eq!(rule("[\"com.microsoft.*\"]").matches(&ctx), Some(55));
+        assert_eq!(rule("[\"*.jetbrains.*\"]").matches(&ctx), None);
+        assert_eq!(rule("[\"com.microsoft\"]").matches(&ctx), None);
+
+        let idea = MatchContext {
+            active_app: Some("IntelliJ IDEA".to_string()),
+            active_app_id: Some("com.jetbrains.intellij".to_string()),
+            ..ctx
+        };
+        assert!(rule("[\"*.jetbrains.*\"]").matches(&idea).is_some());
+        assert!(rule("[\"IntelliJ ID??\"]").matches(&idea).is_some());
+        assert!(rule("[\"IntelliJ\"]").matches(&idea).is_some());
+    }
+
+    #[test]
+    fn url_matches_domains_paths_and_globs() {
+        let url = "https://www.GitHub.com/rust-lang/rust/pull/1";
+        assert!(url_matches("github.com", url));
+        assert!(url_matches("https://github.com/rust-lang/", url));
+        assert!(url_matches("github.com/*/rust/pull/*", url));
+        assert!(!url_matches("github.com/haasonsaas", url));
+        assert!(!url_matches("hub.com", url));
+        assert!(url_matches(
+            "google.com",
+            "https://docs.google.com/document/d/1"
+        ));
+        assert!(url_matches(
+            "*.google.com",
+            "https://docs.google.com/document/d/1"
+        ));
+        assert!(!url_matches("docs.google.com", "https://mail.google.com/"));
+
+        let rule: Rule =
+            toml::from_str("id = \"a\"\nname = \"A\"\n[match]\nurl = [\"github.com\"]").unwrap();
+        let ctx = |url: Option<&str>| MatchContext {
+            text: String::new(),
+            content_types: Vec::new(),
+            active_app: Some("Google Chrome".to_string()),
+            active_app_id: Some("com.google.Chrome".to_string()),
+            active_url: url.map(str::to_string),
+        };
+        assert_eq!(rule.matches(&ctx(Some(url))), Some(55));
+        assert_eq!(rule.matches(&ctx(None)), None);
+    }
+
+    #[test]
+    fn transform_accepts_a_pipeline() {
+        let rule: Rule = toml::from_str(
+            r#"
+id = "yaml_sorted"
+name = "YAML → sorted JSON"
+transform = ["yaml_to_json", "json_sort_keys", { dedupe_lines = {} }]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rule.transforms(),
+            [
+                TransformKind::YamlToJson,
+                TransformKind::JsonSortKeys,
+                TransformKind::DedupeLines { sort: false },
+            ]
+        );
+        let ids = vec![rule.id.clone()];
+        let rules = vec![rule];
+        assert_eq!(fallback_rules(&rules, &ids, "b: 1\na: 2", 3).len(), 1);
+        assert!(fallback_rules(&rules, &ids, "- [", 3).is_empty());
+
+        let single: Rule =
+            toml::from_str("id = \"a\"\nname = \"A\"\ntransform = \"json_prettify\"").unwrap();
+        assert_eq!(single.transforms(), [TransformKind::JsonPrettify]);
+        assert!(
+            toml::to_string(&single)
+                .unwrap()
+                .contains("transform = \"json_prettify\"")
+        );
+        let none: Rule = toml::from_str("id = \"a\"\nname = \"A\"").unwrap();
+        assert!(none.transforms().is_empty());
+        assert!(!toml::to_string(&none).unwrap().contains("transform"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fallback_rules_never_run_external_transforms() {
+        let marker =
+            std::env::temp_dir().join(format!("pasteflow-fallback-{}", std::process::id()));
+        let rule: Rule = toml::from_str(&format!(
+            "id = \"touch\"\nname = \"Touch\"\ntransform = {{ command = {{ program = \"touch\", args = [{:?}] }} }}",
+            marker.display().to_string()
+        ))
+        .unwrap();
+        let ids = vec![rule.id.clone()];
+        assert!(fallback_rules(&[rule], &ids, "text", 3).is_empty());
+        assert!(!marker.exists());
+    }
+}
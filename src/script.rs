@@ -0,0 +1,38 @@
+//! Rhai scripts as transforms. The clipboard is bound to `text` and the value
+//! of the last expression is the output. Scripts get no file, network, or
+//! process access, and run under operation and size limits.
+
+use rhai::{Dynamic, Engine, Scope};
+
+/// Largest string, array, or map a script may build.
+const MAX_VALUE_SIZE: usize = 16 * 1024 * 1024;
+const MAX_CALL_LEVELS: usize = 64;
+
+/// Run `code` with `input` bound to `text`, stopping after `max_operations`.
+pub fn run(code: &str, input: &str, max_operations: u64) -> Result<String, String> {
+    let engine = engine(max_operations);
+    let mut scope = Scope::new();
+    scope.push("text", input.to_string());
+    let value = engine
+        .eval_with_scope::<Dynamic>(&mut scope, code)
+        .map_err(|e| format!("script error: {}", e))?;
+    if value.is_unit() {
+        return Err("script produced no value; end it with the output expression".to_string());
+    }
+    Ok(value.to_string())
+}
+
+fn engine(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(max_operations.max(1));
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_string_size(MAX_VALUE_SIZE);
+    engine.set_max_array_size(MAX_VALUE_SIZE);
+    engine.set_max_map_size(MAX_VALUE_SIZE);
+    // `print` and `debug` would write to the app's stdout; `eval` would let a
+    // script compile code built at runtime.
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+    engine.disable_symbol("eval");
+    engine
+}
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Panel contents saved on quit when `ui.restore_session` is on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub saved_at: DateTime<Utc>,
+    pub input: String,
+    pub output: String,
+    #[serde(default)]
+    pub selected_rule_id: Option<String>,
+    #[serde(default)]
+    pub active_app: Option<String>,
+}
+
+pub fn session_path() -> PathBuf {
+    crate::config::config_path().with_file_name("session.json")
+}
+
+pub fn save(session: &Session) -> io::Result<()> {
+    let path = session_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_vec(session)?)
+}
+
+pub fn load() -> Option<Session> {
+    let raw = fs::read(session_path()).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+pub fn clear() -> io::Result<()> {
+    match fs::remove_file(session_path()) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,447 @@
+//! `pasteflow soak [--iterations N] [--seed N] [--corpus DIR]`: run random and
+//! mutated clipboard text through every transform, the detectors, and the regex
+//! tester, reporting panics, stalls, and runaway output.
+
+use crate::detect::{detect_content_types, parse_timestamp};
+use crate::rules::test_regex;
+use crate::transforms::{
+    Compression, ConflictSide, FlatFormat, HumanizeStyle, ImageEmbedFormat, IndentStyle, LinkStyle,
+    ListStyle, PathStyle, Radix, TimestampFormat, TransformKind,
+};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// A single run slower than this is reported as a stall.
+const SLOW_RUN: Duration = Duration::from_secs(2);
+/// Mutated inputs stop growing past this many chars.
+const MAX_INPUT_CHARS: usize = 16 * 1024;
+
+/// Starting points for mutation, one per content shape the transforms expect.
+const SEEDS: &[&str] = &[
+    "{\"name\": \"Ada\", \"tags\": [1, 2.5, null], \"nested\": {\"ok\": true}}",
+    "// tsconfig\n{compilerOptions: {strict: true, paths: ['a',],},}",
+    "name: Ada\nlist:\n  - a\n  - b\n---\nother: 1",
+    "curl -X POST 'https://example.com/api?q=1' -H 'Content-Type: application/json' --data-raw '{\"a\":1}'",
+    "C:\\Users\\Ada Lovelace\\notes.txt\n/mnt/c/x\\ y\nfile:///tmp/a%20b\n\\\\server\\share",
+    "1700000000\n2024-01-02T03:04:05Z\n2 hours ago",
+    "\u{1b}[31mred\u{1b}[0m plain \u{1b}]0;title\u{7}",
+    "• one\n* two\n3) three\n  - nested",
+    "  12: fn main() {\n  13: \trun();\n  14: }",
+    "> quoted\n>> twice\nplain",
+    "a\n<<<<<<< HEAD\nours\n||||||| base\nbase\n=======\ntheirs\n>>>>>>> main\nz",
+    "\"It's\" -- a 'test'... « déjà » ```code```",
+    "550e8400-e29b-41d4-a716-446655440000 0xFF 0b1010 0o17 -42",
+    "Lovelace, Ada\nHopper, Grace",
+    "| Name | Age |\n|:---|---:|\n| Ada \\| L | 36 |\n| Grace |",
+    "id,Name,email\n1,Ada,\"ada@example.com\"\n2,\"Hopper, Grace\",\"multi\nline\"",
+    "?tag=a&tag=b&name=%C3%A9",
+    "ship it :rocket: :+1::skin-tone-3: 👨\u{200d}👩\u{200d}👧 1\u{fe0f}\u{20e3} ©\u{fe0f} ™ 12:30:45",
+    "See [docs](https://example.com/a_(b) \"T\") ![i][1] [x][]\n\n[1]: /i.png\n[x]: </x y>",
+    "Summe: 1.234,56 € -$1,234.56 1\u{202f}234,5 CHF 1'234.50 2024-01-05 v1.2.3",
+    "10.0.0.5/24 fe80::1%eth0 2001:DB8:0:0::1/64 ::ffff:192.0.2.1 12:30:45 0.0.0.0/0\n::/0",
+    "Teh  report is definately done, TEH END. `teh  code` alot\n",
+    "app.name = \"pasteflow\"\nservers[1].port = 8080\n[\"x.y\"][0] = true",
+    "H4sIAAAAAAACA8tIzcnJBwCGphA2BQAAAA==",
+    "日本語のテキスト 😀 e\u{301}\r\n\tmixed\u{a0}spaces\u{202f}",
+];
+
+/// Characters mutations insert: syntax the parsers key on, control characters,
+/// and multi-byte text that trips byte-offset slicing.
+const PALETTE: &[char] = &[
+    '"', '\'', '\\', '{', '}', '[', ']', ':', ',', '-', '.', '\n', '\r', '\t', ' ', '\0', '\u{1b}',
+    'é', '€', '😀', '\u{202f}', '\u{301}', '\u{feff}', '«', '—', '$', '(', ')', '*', '?', '0', '9',
+    'x', '%', '/', '>', '#', '`', '|', '+',
+];
+
+#[derive(Debug)]
+struct Failure {
+    target: String,
+    problem: String,
+    input: String,
+}
+
+/// xorshift64*, so a failing run can be replayed with `--seed`.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut iterations = 1000;
+    let mut seed = None;
+    let mut corpus = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => iterations = parse_number(args.next(), "--iterations")? as usize,
+            "--seed" => seed = Some(parse_number(args.next(), "--seed")?),
+            "--corpus" => corpus = args.next().map(PathBuf::from),
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or(1)
+    });
+
+    let mut seeds: Vec<String> = SEEDS.iter().map(|s| s.to_string()).collect();
+    if let Some(dir) = corpus {
+        let mut files = Vec::new();
+        crate::corpus::collect_files(&dir, &mut files)
+            .map_err(|e| format!("{}: {}", dir.display(), e))?;
+        for file in files {
+            let bytes = fs::read(&file).map_err(|e| format!("{}: {}", file.display(), e))?;
+            seeds.push(String::from_utf8_lossy(&bytes).into_owned());
+        }
+    }
+
+    // Panics are collected as failures; keep the default hook from printing each one.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let (runs, failures) = soak(&seeds, iterations, seed);
+    panic::set_hook(hook);
+
+    for failure in &failures {
+        println!("{}: {}", failure.target, failure.problem);
+        println!("  input: {:?}", preview(&failure.input));
+    }
+    println!(
+        "{} runs over {} inputs, {} failures (--seed {})",
+        runs,
+        iterations,
+        failures.len(),
+        seed
+    );
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} failures", failures.len()))
+    }
+}
+
+fn parse_number(value: Option<&String>, flag: &str) -> Result<u64, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} needs a number", flag))
+}
+
+fn preview(input: &str) -> String {
+    let mut out: String = input.chars().take(300).collect();
+    if out.len() < input.len() {
+        out.push('…');
+    }
+    out
+}
+
+/// Run `iterations` mutated inputs through every target; returns the run count
+/// and whatever failed.
+fn soak(seeds: &[String], iterations: usize, seed: u64) -> (usize, Vec<Failure>) {
+    let mut rng = Rng::new(seed);
+    let transforms = transforms();
+    let mut runs = 0;
+    let mut failures = Vec::new();
+    for _ in 0..iterations {
+        let input = mutate(&mut rng, seeds);
+        let pattern = input.lines().next().unwrap_or("").to_string();
+        let sample = &seeds[rng.below(seeds.len())];
+
+        let mut check = |target: &str, call: &mut dyn FnMut() -> usize| {
+            runs += 1;
+            let started = Instant::now();
+            let problem = match panic::catch_unwind(AssertUnwindSafe(call)) {
+                Err(payload) => Some(format!("panicked: {}", panic_message(&*payload))),
+                // Generous, but catches exponential blowups.
+                Ok(len) if len > 64 * input.len() + (1 << 20) => {
+                    Some(format!("output grew to {} bytes", len))
+                }
+                Ok(_) if started.elapsed() > SLOW_RUN => {
+                    Some(format!("took {:?}", started.elapsed()))
+                }
+                Ok(_) => None,
+            };
+            if let Some(problem) = problem {
+                failures.push(Failure {
+                    target: target.to_string(),
+                    problem,
+                    input: input.clone(),
+                });
+            }
+        };
+
+        check("detect", &mut || detect_content_types(&input).len());
+        check("parse_timestamp", &mut || {
+            parse_timestamp(&input, crate::clock::global(), false).map_or(0, |_| 1)
+        });
+        check("test_regex", &mut || {
+            test_regex(&pattern, sample).map_or(0, |test| test.matches.len())
+        });
+        let user_regexes = [
+            TransformKind::RegexReplace {
+                pattern: pattern.clone(),
+                replacement: "[$0]".to_string(),
+                flags: "m".to_string(),
+            },
+            TransformKind::ExtractTemplate {
+                pattern: pattern.clone(),
+                template: "<$0>".to_string(),
+                flags: String::new(),
+                keep_unmatched: true,
+            },
+        ];
+        for kind in transforms.iter().chain(&user_regexes) {
+            check(&kind_name(kind), &mut || {
+                kind.apply(&input).map_or(0, |output| output.len())
+            });
+        }
+    }
+    (runs, failures)
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic".to_string())
+}
+
+fn mutate(rng: &mut Rng, seeds: &[String]) -> String {
+    let mut chars: Vec<char> = seeds[rng.below(seeds.len())].chars().collect();
+    for _ in 0..=rng.below(8) {
+        let at = rng.below(chars.len() + 1);
+        match rng.below(6) {
+            0 => chars.insert(at, PALETTE[rng.below(PALETTE.len())]),
+            1 => {
+                let end = (at + 1 + rng.below(8)).min(chars.len());
+                chars.drain(at.min(end)..end);
+            }
+            2 => {
+                let end = (at + 1 + rng.below(64)).min(chars.len());
+                let copy: Vec<char> = chars[at.min(end)..end].to_vec();
+                chars.splice(at..at, copy);
+            }
+            3 => {
+                let other: Vec<char> = seeds[rng.below(seeds.len())].chars().collect();
+                let start = rng.below(other.len());
+                let end = (start + 1 + rng.below(32)).min(other.len());
+                chars.splice(at..at, other[start..end].iter().copied());
+            }
+            4 if at < chars.len() => chars[at] = PALETTE[rng.below(PALETTE.len())],
+            _ => {
+                if chars.len() < MAX_INPUT_CHARS / 2 {
+                    chars.extend_from_within(..);
+                }
+            }
+        }
+    }
+    chars.truncate(MAX_INPUT_CHARS);
+    chars.into_iter().collect()
+}
+
+fn kind_name(kind: &TransformKind) -> String {
+    match serde_json::to_value(kind) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(serde_json::Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        _ => "transform".to_string(),
+    }
+}
+
+/// Every transform, with options chosen to reach the most code. `Command` and
+/// `Plugin` are left out: they would only exercise the program or module they run.
+pub fn transforms() -> Vec<TransformKind> {
+    vec![
+        TransformKind::JsonPrettify,
+        TransformKind::JsonMinify,
+        TransformKind::Json5ToJson,
+        TransformKind::JsonSortKeys,
+        TransformKind::JsonToYaml,
+        TransformKind::YamlToJson,
+        TransformKind::YamlPrettify { sort_keys: true },
+        TransformKind::StripFormatting,
+        TransformKind::CollapseWhitespace,
+        TransformKind::StripAnsi,
+        TransformKind::HtmlToText,
+        TransformKind::RedactSecrets,
+        TransformKind::BulletNormalize,
+        TransformKind::ListConvert {
+            to: ListStyle::Numbered,
+        },
+        TransformKind::ListConvert {
+            to: ListStyle::Bullets,
+        },
+        TransformKind::TimestampNormalize {
+            format: TimestampFormat::Auto,
+            timezone: None,
+            day_first: false,
+        },
+        TransformKind::TimestampNormalize {
+            format: TimestampFormat::Strftime("%A %e %B %Y %H:%M %Z".to_string()),
+            timezone: Some("Europe/Berlin".to_string()),
+            day_first: true,
+        },
+        TransformKind::DedupeLines { sort: true },
+        TransformKind::WhitespaceCleanup {
+            indent: IndentStyle::Tabs,
+            tab_width: 4,
+            trim_trailing: true,
+            final_newline: true,
+        },
+        TransformKind::WhitespaceCleanup {
+            indent: IndentStyle::Spaces,
+            tab_width: 0,
+            trim_trailing: false,
+            final_newline: false,
+        },
+        TransformKind::Translate {
+            to: "en".to_string(),
+        },
+        TransformKind::Summarize { max_words: 60 },
+        TransformKind::ImageEmbed {
+            format: ImageEmbedFormat::Markdown,
+            dir: "~/Pictures/pasteflow".to_string(),
+            upload_command: None,
+        },
+        TransformKind::Sha256,
+        TransformKind::Sha1,
+        TransformKind::Md5,
+        TransformKind::TextStats,
+        TransformKind::Ipv6Format { expand: true },
+        TransformKind::Ipv6Format { expand: false },
+        TransformKind::CidrRange,
+        TransformKind::SortIps,
+        TransformKind::RedactIps,
+        TransformKind::UuidGenerate,
+        TransformKind::UuidFormat {
+            hyphens: false,
+            uppercase: true,
+        },
+        TransformKind::Truncate {
+            max_chars: Some(7),
+            max_lines: Some(2),
+            ellipsis: "…".to_string(),
+            words: true,
+        },
+        TransformKind::WrapText { width: 1 },
+        TransformKind::WrapText { width: 80 },
+        TransformKind::AddLineNumbers {
+            start: usize::MAX - 1,
+            separator: ": ".to_string(),
+        },
+        TransformKind::StripLineNumbers,
+        TransformKind::Indent {
+            width: 4,
+            tabs: false,
+        },
+        TransformKind::Dedent { width: 4 },
+        TransformKind::Dedent { width: 0 },
+        TransformKind::QuoteLines,
+        TransformKind::UnquoteLines { all: true },
+        TransformKind::ResolveConflicts {
+            keep: ConflictSide::Both,
+        },
+        TransformKind::TitleCase,
+        TransformKind::SentenceCase,
+        TransformKind::UpperCase,
+        TransformKind::LowerCase,
+        TransformKind::Typographize {
+            locale: "fr".to_string(),
+        },
+        TransformKind::Typographize {
+            locale: "de".to_string(),
+        },
+        TransformKind::AsciiPunctuation,
+        TransformKind::Autocorrect {
+            corrections: [("pasteflo".to_string(), "pasteflow".to_string())].into(),
+        },
+        TransformKind::ShortcodesToEmoji,
+        TransformKind::EmojiToShortcodes,
+        TransformKind::MarkdownLinks {
+            to: LinkStyle::Reference,
+        },
+        TransformKind::MarkdownLinks {
+            to: LinkStyle::Inline,
+        },
+        TransformKind::Rot13,
+        TransformKind::Caesar { shift: -3 },
+        TransformKind::PathConvert {
+            to: PathStyle::Posix,
+        },
+        TransformKind::PathConvert {
+            to: PathStyle::Windows,
+        },
+        TransformKind::PathConvert {
+            to: PathStyle::FileUrl,
+        },
+        TransformKind::QueryToJson,
+        TransformKind::CurlToJson,
+        TransformKind::JsonToCurl,
+        TransformKind::JsonFlatten {
+            format: FlatFormat::Json,
+        },
+        TransformKind::JsonUnflatten,
+        TransformKind::CsvColumns {
+            keep: vec!["email".to_string(), "name".to_string()],
+            drop: vec!["name".to_string()],
+        },
+        TransformKind::TableTranspose,
+        TransformKind::DecompressBase64,
+        TransformKind::CompressBase64 {
+            format: Compression::Deflate,
+        },
+        TransformKind::HumanizeNumbers {
+            style: HumanizeStyle::BinaryBytes,
+        },
+        TransformKind::ParseHumanNumbers,
+        TransformKind::ParseLocaleNumbers {
+            locale: "fr".to_string(),
+        },
+        TransformKind::FormatLocaleNumbers {
+            locale: "de-CH".to_string(),
+            currency: Some("CHF".to_string()),
+        },
+        TransformKind::NumberBase {
+            to: Radix::Binary,
+            prefix: true,
+        },
+        TransformKind::NumberBase {
+            to: Radix::Decimal,
+            prefix: false,
+        },
+        TransformKind::Script {
+            code: "let lines = text.split(\"\\n\"); lines.reverse(); `${lines.len()}: ${lines}`"
+                .to_string(),
+            max_operations: 10_000,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SEEDS, soak};
+
+    #[test]
+    fn mutated_inputs_do_not_crash_transforms() {
+        let seeds: Vec<String> = SEEDS.iter().map(|s| s.to_string()).collect();
+        let (runs, failures) = soak(&seeds, 300, 2045);
+        assert!(runs > 300);
+        assert!(failures.is_empty(), "{:#?}", failures);
+    }
+}
@@ -0,0 +1,227 @@
+//! Tables pasted from spreadsheets, exports, and docs: Markdown when the
+//! second line is a `|---|` separator, TSV when the first line has a tab, CSV
+//! otherwise. The first row is the header.
+
+use csv::{ReaderBuilder, Terminator, WriterBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Tsv,
+    Markdown,
+}
+
+impl Format {
+    fn delimiter(self) -> u8 {
+        match self {
+            Format::Csv | Format::Markdown => b',',
+            Format::Tsv => b'\t',
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Format::Csv => "CSV",
+            Format::Tsv => "TSV",
+            Format::Markdown => "Markdown table",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub format: Format,
+    pub rows: Vec<Vec<String>>,
+    trailing_newline: bool,
+}
+
+pub fn parse(input: &str) -> Result<Table, String> {
+    let mut lines = input.lines().filter(|line| !line.trim().is_empty());
+    let first = lines.next().ok_or("no table found")?;
+    let trailing_newline = input.ends_with('\n');
+    if first.contains('|') && lines.next().is_some_and(is_markdown_separator) {
+        return Ok(Table {
+            format: Format::Markdown,
+            rows: parse_markdown(input),
+            trailing_newline,
+        });
+    }
+    let format = if first.contains('\t') {
+        Format::Tsv
+    } else if first.contains(',') {
+        Format::Csv
+    } else {
+        return Err("expected comma- or tab-separated columns or a Markdown table".to_string());
+    };
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(format.delimiter())
+        .from_reader(input.trim_start().as_bytes());
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("invalid {}: {}", format.name(), e))?;
+        rows.push(record.iter().map(str::to_string).collect());
+    }
+    Ok(Table {
+        format,
+        rows,
+        trailing_newline,
+    })
+}
+
+/// `|---|:--:|` or `--- | ---`: dashes, colons, and spaces between pipes.
+fn is_markdown_separator(line: &str) -> bool {
+    line.contains('-')
+        && line.contains('|')
+        && line
+            .trim()
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+fn parse_markdown(input: &str) -> Vec<Vec<String>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .filter(|(i, _)| *i != 1)
+        .map(|(_, line)| markdown_cells(line))
+        .collect()
+}
+
+/// Cells of one `| a | b |` row; `\|` is a literal pipe.
+fn markdown_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => line,
+    };
+    let mut cells = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cells.last_mut().unwrap().push('|');
+                chars.next();
+            }
+            '|' => cells.push(String::new()),
+            c => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+impl Table {
+    /// Write the table back in its own format: CSV and TSV quote only where
+    /// needed, Markdown columns are padded to line up.
+    pub fn render(&self) -> String {
+        let mut out = match self.format {
+            Format::Markdown => self.render_markdown(),
+            Format::Csv | Format::Tsv => self.render_delimited(),
+        };
+        if !self.trailing_newline && out.ends_with('\n') {
+            out.pop();
+        } else if self.trailing_newline && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out
+    }
+
+    fn render_delimited(&self) -> String {
+        let mut writer = WriterBuilder::new()
+            .flexible(true)
+            .delimiter(self.format.delimiter())
+            .terminator(Terminator::Any(b'\n'))
+            .from_writer(Vec::new());
+        for row in &self.rows {
+            // Writing to a Vec cannot fail.
+            let _ = writer.write_record(row);
+        }
+        let bytes = writer.into_inner().unwrap_or_default();
+        String::from_utf8(bytes).unwrap_or_default()
+    }
+
+    fn render_markdown(&self) -> String {
+        let columns = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let cell = |row: &[String], i: usize| {
+            row.get(i)
+                .map(|cell| cell.replace('|', "\\|").replace('\n', "<br>"))
+                .unwrap_or_default()
+        };
+        let mut widths = vec![3; columns];
+        for row in &self.rows {
+            for (i, width) in widths.iter_mut().enumerate() {
+                *width = (*width).max(cell(row, i).chars().count());
+            }
+        }
+        let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+        let mut lines = Vec::with_capacity(self.rows.len() + 1);
+        for (n, row) in self.rows.iter().enumerate() {
+            lines.push(line(
+                widths
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &width)| {
+                        let text = cell(row, i);
+                        let pad = width - text.chars().count();
+                        format!("{}{}", text, " ".repeat(pad))
+                    })
+                    .collect(),
+            ));
+            if n == 0 {
+                lines.push(line(widths.iter().map(|&w| "-".repeat(w)).collect()));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Keep the `keep` columns in that order (every column when empty), minus
+    /// the `drop` columns. Header names match ignoring case and surrounding space.
+    pub fn select_columns(&mut self, keep: &[String], drop: &[String]) -> Result<(), String> {
+        let header = self.rows.first().ok_or("no table found")?;
+        let find = |name: &String| {
+            header
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| {
+                    format!(
+                        "no column '{}'; the columns are {}",
+                        name,
+                        header.join(", ")
+                    )
+                })
+        };
+        let mut indices: Vec<usize> = if keep.is_empty() {
+            (0..header.len()).collect()
+        } else {
+            keep.iter().map(find).collect::<Result<_, _>>()?
+        };
+        let dropped: Vec<usize> = drop.iter().map(find).collect::<Result<_, _>>()?;
+        indices.retain(|i| !dropped.contains(i));
+        if indices.is_empty() {
+            return Err("no columns left".to_string());
+        }
+        for row in &mut self.rows {
+            *row = indices
+                .iter()
+                .map(|&i| row.get(i).cloned().unwrap_or_default())
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Swap rows and columns; short rows are padded with empty cells.
+    pub fn transpose(&mut self) {
+        let columns = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        self.rows = (0..columns)
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .map(|row| row.get(i).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+    }
+}